@@ -14,6 +14,7 @@ pub enum Subcommand {
     Init(InitCommand),
     List(ListCommand),
     Validate(ValidateCommand),
+    Snooze(SnoozeCommand),
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
@@ -29,6 +30,30 @@ pub struct RunCommand {
     /// sort by deadline (default: false)
     #[argh(switch)]
     pub sort_by_deadline: Option<bool>,
+    /// number of threads to search with (default: chosen automatically)
+    #[argh(option, short = 'j')]
+    pub threads: Option<usize>,
+    /// only report reminders added or changed since the merge base with this git ref
+    #[argh(option)]
+    pub since: Option<String>,
+    /// attribute reminders to their last author with git blame (default: false)
+    #[argh(switch)]
+    pub blame: Option<bool>,
+    /// reuse reminders of unchanged files from .reminder-lint/cache (default: false)
+    #[argh(switch)]
+    pub cache: Option<bool>,
+    /// read the content to check from stdin instead of searching files
+    #[argh(switch)]
+    pub stdin: bool,
+    /// file path reported for stdin content, also used to pick its comment syntax
+    #[argh(option)]
+    pub stdin_filename: Option<String>,
+    /// exit with status 1 when any diagnostic is reported (default: false)
+    #[argh(switch)]
+    pub fail_on_diagnostics: bool,
+    /// path to the snooze file (default: ./.remindsnooze)
+    #[argh(option)]
+    pub snooze_file_path: Option<String>,
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
@@ -49,6 +74,30 @@ pub struct ListCommand {
     /// output in json format
     #[argh(switch)]
     pub json: bool,
+    /// number of threads to search with (default: chosen automatically)
+    #[argh(option, short = 'j')]
+    pub threads: Option<usize>,
+    /// only report reminders added or changed since the merge base with this git ref
+    #[argh(option)]
+    pub since: Option<String>,
+    /// attribute reminders to their last author with git blame (default: false)
+    #[argh(switch)]
+    pub blame: Option<bool>,
+    /// reuse reminders of unchanged files from .reminder-lint/cache (default: false)
+    #[argh(switch)]
+    pub cache: Option<bool>,
+    /// read the content to check from stdin instead of searching files
+    #[argh(switch)]
+    pub stdin: bool,
+    /// file path reported for stdin content, also used to pick its comment syntax
+    #[argh(option)]
+    pub stdin_filename: Option<String>,
+    /// exit with status 1 when any diagnostic is reported (default: false)
+    #[argh(switch)]
+    pub fail_on_diagnostics: bool,
+    /// path to the snooze file (default: ./.remindsnooze)
+    #[argh(option)]
+    pub snooze_file_path: Option<String>,
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
@@ -64,6 +113,48 @@ pub struct ValidateCommand {
     /// sort by deadline (default: false)
     #[argh(switch)]
     pub sort_by_deadline: Option<bool>,
+    /// number of threads to search with (default: chosen automatically)
+    #[argh(option, short = 'j')]
+    pub threads: Option<usize>,
+    /// only report reminders added or changed since the merge base with this git ref
+    #[argh(option)]
+    pub since: Option<String>,
+    /// fall back to the last author from git blame for a missing assignee (default: false)
+    #[argh(switch)]
+    pub blame: Option<bool>,
+    /// reuse reminders of unchanged files from .reminder-lint/cache (default: false)
+    #[argh(switch)]
+    pub cache: Option<bool>,
+    /// read the content to check from stdin instead of searching files
+    #[argh(switch)]
+    pub stdin: bool,
+    /// file path reported for stdin content, also used to pick its comment syntax
+    #[argh(option)]
+    pub stdin_filename: Option<String>,
+    /// exit with status 1 when any diagnostic is reported (default: false)
+    #[argh(switch)]
+    pub fail_on_diagnostics: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+#[argh(subcommand, name = "snooze")]
+/// postpone a reminder until a day without editing its source
+pub struct SnoozeCommand {
+    /// the reminder to snooze, as path:line
+    #[argh(positional)]
+    pub target: String,
+    /// the day the snooze ends, as YYYY-MM-DD
+    #[argh(option)]
+    pub until: String,
+    /// why the reminder is snoozed
+    #[argh(option)]
+    pub reason: Option<String>,
+    /// path to the config file (default: ./remind.yaml or ./remind.yml)
+    #[argh(option, short = 'c')]
+    pub config_file_path: Option<String>,
+    /// path to the snooze file (default: ./.remindsnooze)
+    #[argh(option)]
+    pub snooze_file_path: Option<String>,
 }
 
 impl Args {
@@ -5,7 +5,8 @@ use promptuity::prompts::{Confirm, Input, Select, SelectOption};
 use promptuity::themes::FancyTheme;
 use promptuity::{Promptuity, Term};
 use reminder_lint_core::config::builder::{
-    ConfigBuilder, FileConfig, ValidateItem, DEFAULT_CONFIG_FILE_PATHS, DEFAULT_IGNORE_FILE_PATH,
+    ConfigBuilder, DatetimeFormat, FileConfig, ValidateItem, DEFAULT_CONFIG_FILE_PATHS,
+    DEFAULT_IGNORE_FILE_PATH,
 };
 
 struct InitPromptResult {
@@ -80,14 +81,32 @@ fn init_prompt() -> Result<InitPromptResult, Error> {
             "datetime".to_string(),
             ValidateItem {
                 format: datetime_format.clone(),
+                ..Default::default()
             },
         );
 
         result.config = Some(FileConfig {
             comment_regex,
-            datetime_format,
+            datetime_format: DatetimeFormat::Single(datetime_format),
+            timezone: default_config.timezone,
+            milestones: default_config.milestones,
+            fiscal_year_start: default_config.fiscal_year_start,
             search_directory,
+            search_directories: vec![],
+            include: vec![],
+            exclude: vec![],
             remind_if_no_date,
+            comment_aware: default_config.comment_aware,
+            comment_syntaxes: default_config.comment_syntaxes,
+            multiline: default_config.multiline,
+            continuation_marker: default_config.continuation_marker,
+            version_source: default_config.version_source,
+            issue_tracker: default_config.issue_tracker,
+            warn_before: default_config.warn_before,
+            error_after: default_config.error_after,
+            recurrence_window: default_config.recurrence_window,
+            blame: default_config.blame,
+            cache: default_config.cache,
             validates,
         });
     }
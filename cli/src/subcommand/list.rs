@@ -1,51 +1,72 @@
+use super::{collect_reminds, now, remind_line, report_diagnostics};
 use crate::args::ListCommand;
 use anyhow::Error;
 use reminder_lint_core::config::builder::ConfigBuilder;
+use reminder_lint_core::remind::SnoozeFile;
 use serde::Serialize;
 
 #[derive(Debug, Serialize)]
 struct RemindList {
     pub expired: Vec<reminder_lint_core::remind::Remind>,
     pub upcoming: Vec<reminder_lint_core::remind::Remind>,
+    pub diagnostics: Vec<reminder_lint_core::remind::Diagnostic>,
 }
 
 pub fn execute_list(command: ListCommand) -> Result<(), Error> {
     let conf = ConfigBuilder::new()
         .config_file_path(command.config_file_path)
         .ignore_file_path(command.ignore_file_path)
+        .snooze_file_path(command.snooze_file_path)
+        .threads(command.threads)
+        .since(command.since)
+        .cache(command.cache)
+        .blame(command.blame)
         .build()?;
 
     let mut expired = Vec::new();
     let mut upcoming = Vec::new();
 
-    for remind in reminder_lint_core::reminders(&conf)?.reminds {
-        if remind.datetime < chrono::Local::now().timestamp() {
+    let snoozes = SnoozeFile::load(conf.snooze_file_path())?;
+    let collected = collect_reminds(&conf, command.stdin, command.stdin_filename)?;
+    let now = now();
+    for remind in collected.reminds {
+        // Like `run`, a snoozed reminder is upcoming until its snooze ends.
+        let snoozed = snoozes.snoozed(&remind, now, conf.timezone()).is_some();
+        if remind.is_expired(now) && !snoozed {
             expired.push(remind);
         } else {
             upcoming.push(remind);
         }
     }
 
-    let reminders = RemindList { expired, upcoming };
+    let reminders = RemindList {
+        expired,
+        upcoming,
+        diagnostics: collected.diagnostics,
+    };
 
     if command.json {
         println!("{}", serde_json::to_string(&reminders)?);
+        exit_on_diagnostics(command.fail_on_diagnostics, &reminders);
         return Ok(());
     }
 
     for remind in &reminders.expired {
-        println!(
-            "{}:{} {}",
-            remind.position.file, remind.position.line, remind.message
-        );
+        println!("{}", remind_line(&conf, remind));
     }
 
     for remind in &reminders.upcoming {
-        println!(
-            "{}:{} {}",
-            remind.position.file, remind.position.line, remind.message
-        );
+        println!("{}", remind_line(&conf, remind));
     }
 
+    report_diagnostics(&reminders.diagnostics);
+    exit_on_diagnostics(command.fail_on_diagnostics, &reminders);
+
     Ok(())
 }
+
+fn exit_on_diagnostics(fail_on_diagnostics: bool, reminders: &RemindList) {
+    if fail_on_diagnostics && !reminders.diagnostics.is_empty() {
+        std::process::exit(1);
+    }
+}
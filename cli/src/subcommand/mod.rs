@@ -1,3 +1,11 @@
+use std::io::Read;
+
+use anyhow::Error;
+use reminder_lint_core::{
+    config::Config,
+    remind::{Diagnostic, Remind},
+    Reminders,
+};
 use validate::execute_validates;
 
 use crate::{
@@ -5,11 +13,12 @@ use crate::{
     print::{pretty_print, Status},
 };
 
-use self::{init::execute_init, list::execute_list, run::execute_run};
+use self::{init::execute_init, list::execute_list, run::execute_run, snooze::execute_snooze};
 
 mod init;
 mod list;
 mod run;
+mod snooze;
 mod validate;
 
 pub fn execute_subcommand(subcommand: Subcommand) {
@@ -18,6 +27,7 @@ pub fn execute_subcommand(subcommand: Subcommand) {
         Subcommand::Init(command) => execute_init(command),
         Subcommand::List(command) => execute_list(command),
         Subcommand::Validate(command) => execute_validates(command),
+        Subcommand::Snooze(command) => execute_snooze(command),
     };
 
     if let Err(e) = result {
@@ -25,3 +35,50 @@ pub fn execute_subcommand(subcommand: Subcommand) {
         std::process::exit(1);
     }
 }
+
+const DEFAULT_STDIN_FILENAME: &str = "<stdin>";
+
+// Reads the reminders either from the searched files or, with `--stdin`, from standard input.
+fn collect_reminds(
+    conf: &Config,
+    stdin: bool,
+    stdin_filename: Option<String>,
+) -> Result<Reminders, Error> {
+    if !stdin {
+        return Ok(reminder_lint_core::reminders(conf)?);
+    }
+
+    let mut text = String::new();
+    std::io::stdin().read_to_string(&mut text)?;
+    let filename = stdin_filename.unwrap_or_else(|| DEFAULT_STDIN_FILENAME.to_string());
+
+    Ok(reminder_lint_core::scan_text(conf, &filename, &text)?)
+}
+
+// The line a reminder is reported with. With `--blame`, the assignee is appended, falling back
+// to the last author of the line.
+fn remind_line(conf: &Config, remind: &Remind) -> String {
+    let location = format!("{}:{}", remind.position.file, remind.position.line);
+    match remind.assignee().filter(|_| conf.blame()) {
+        Some(assignee) => format!(
+            "{} {} (assignee: {})",
+            location,
+            remind.message.trim_end(),
+            assignee
+        ),
+        None => format!("{} {}", location, remind.message),
+    }
+}
+
+// Prints the problems found while scanning, the same way for every subcommand.
+fn report_diagnostics(diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        pretty_print(diagnostic, Status::Warning);
+    }
+}
+
+// The single clock every subcommand decides expiry against. Deadlines are instants, so the
+// zone of the machine running the check does not matter.
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
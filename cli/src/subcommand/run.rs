@@ -1,30 +1,78 @@
 use anyhow::Error;
 use reminder_lint_core::config::builder::ConfigBuilder;
+use reminder_lint_core::remind::{Severity, SnoozeFile};
 
+use super::{collect_reminds, now, remind_line, report_diagnostics};
 use crate::args::RunCommand;
+use crate::print::{pretty_print, Status};
 
 pub fn execute_run(command: RunCommand) -> Result<(), Error> {
+    // Snoozes for files outside a partial scan are not stale.
+    let partial_scan = command.stdin || command.since.is_some();
     let conf = ConfigBuilder::new()
         .config_file_path(command.config_file_path)
         .ignore_file_path(command.ignore_file_path)
+        .snooze_file_path(command.snooze_file_path)
+        .threads(command.threads)
+        .since(command.since)
+        .cache(command.cache)
+        .blame(command.blame)
         .sort_by_deadline(command.sort_by_deadline)
         .build()?;
 
-    let reminders = reminder_lint_core::reminders(&conf)?;
-    let expired = reminders
-        .reminds
-        .iter()
-        .filter(|remind| remind.datetime < chrono::Local::now().timestamp())
-        .collect::<Vec<_>>();
+    let snoozes = SnoozeFile::load(conf.snooze_file_path())?;
+    let reminders = collect_reminds(&conf, command.stdin, command.stdin_filename)?;
+    let now = now();
+    let mut highest = None;
 
-    for remind in &expired {
-        println!(
-            "{}:{} {}",
-            remind.position.file, remind.position.line, remind.message
-        );
+    for remind in &reminders.reminds {
+        let Some(severity) = remind.severity(now, &conf) else {
+            continue;
+        };
+        let mut line = remind_line(&conf, remind);
+        // A snoozed reminder is upcoming until its snooze ends, so it is reported as a warning
+        // at most.
+        let snooze = snoozes.snoozed(remind, now, conf.timezone());
+        let severity = match snooze {
+            Some(snooze) => {
+                line = format!("{} (snoozed until {})", line.trim_end(), snooze.until);
+                Severity::Warning
+            }
+            None => severity,
+        };
+        // Errors keep the plain format that scripts and CI annotations already parse.
+        match severity {
+            Severity::Error => println!("{}", line),
+            Severity::Warning => pretty_print(line, Status::Warning),
+        }
+        if let Some(invalid) = &remind.invalid_datetime {
+            pretty_print(
+                format!(
+                    "{}:{} invalid datetime: {}",
+                    remind.position.file, remind.position.line, invalid
+                ),
+                Status::Error,
+            );
+        }
+        highest = highest.max(Some(severity));
     }
 
-    if !expired.is_empty() {
+    if !partial_scan {
+        for snooze in snoozes.stale(&reminders.reminds) {
+            pretty_print(
+                format!(
+                    "{}: snooze for {} no longer matches any reminder",
+                    conf.snooze_file_path(),
+                    snooze.target()
+                ),
+                Status::Warning,
+            );
+        }
+    }
+    report_diagnostics(&reminders.diagnostics);
+
+    let failed_diagnostics = command.fail_on_diagnostics && !reminders.diagnostics.is_empty();
+    if highest == Some(Severity::Error) || failed_diagnostics {
         std::process::exit(1);
     }
 
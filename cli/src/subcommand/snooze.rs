@@ -0,0 +1,50 @@
+use anyhow::Error;
+use reminder_lint_core::config::builder::ConfigBuilder;
+use reminder_lint_core::remind::{parse_until, Snooze, SnoozeFile};
+
+use crate::args::SnoozeCommand;
+use crate::print::{pretty_print, Status};
+
+pub fn execute_snooze(command: SnoozeCommand) -> Result<(), Error> {
+    let conf = ConfigBuilder::new()
+        .config_file_path(command.config_file_path)
+        .snooze_file_path(command.snooze_file_path)
+        // A reminder without a date can be snoozed as well.
+        .remind_if_no_date(Some(true))
+        .build()?;
+
+    let (file, line) = command
+        .target
+        .rsplit_once(':')
+        .and_then(|(file, line)| Some((file, line.parse::<u64>().ok()?)))
+        .ok_or_else(|| Error::msg(format!("expected path:line, got `{}`", command.target)))?;
+    parse_until(&command.until)?;
+
+    let text = std::fs::read_to_string(file)?;
+    let reminders = reminder_lint_core::scan_text(&conf, file, &text)?;
+    let remind = reminders
+        .reminds
+        .iter()
+        .find(|r| (r.position.line..=r.position.end_line).contains(&line))
+        .ok_or_else(|| Error::msg(format!("no reminder at {}:{}", file, line)))?;
+
+    let path = conf.snooze_file_path();
+    let mut snoozes = SnoozeFile::load(path)?;
+    snoozes.insert(Snooze {
+        fingerprint: Some(remind.fingerprint()),
+        file: Some(file.to_string()),
+        line: Some(remind.position.line),
+        until: command.until.clone(),
+        reason: command.reason.unwrap_or_default(),
+    });
+    snoozes.save(path)?;
+
+    pretty_print(
+        format!(
+            "Snoozed {}:{} until {} in {}",
+            file, remind.position.line, command.until, path
+        ),
+        Status::Success,
+    );
+    Ok(())
+}
@@ -1,41 +1,38 @@
-use std::collections::HashMap;
-
+use super::{collect_reminds, report_diagnostics};
 use crate::{args::ValidateCommand, print::pretty_print};
 use anyhow::Error;
-use regex::Regex;
 use reminder_lint_core::{
-    config::builder::{ConfigBuilder, ValidateItem},
-    remind::datetime_format_to_regex,
+    config::builder::ConfigBuilder,
+    remind::{ValidationFailure, Validator},
 };
 
 struct InvalidRemind {
     pub remind: reminder_lint_core::remind::Remind,
-    pub unmatched: HashMap<String, ValidateItem>,
+    pub failures: Vec<ValidationFailure>,
 }
 
 pub fn execute_validates(command: ValidateCommand) -> Result<(), Error> {
     let conf = ConfigBuilder::new()
         .config_file_path(command.config_file_path)
         .ignore_file_path(command.ignore_file_path)
+        .threads(command.threads)
+        .since(command.since)
+        .cache(command.cache)
+        .blame(command.blame)
         .sort_by_deadline(command.sort_by_deadline)
         .remind_if_no_date(Some(true)) // Always validates even if no date
         .build()?;
 
-    let reminders = reminder_lint_core::reminders(&conf)?;
+    let validator = Validator::new(conf.validates())?;
+    let reminders = collect_reminds(&conf, command.stdin, command.stdin_filename)?;
     let mut invalid_reminds = Vec::new();
 
-    for remind in reminders.reminds {
-        let mut unmatched = HashMap::new();
-        for (name, validates) in conf.validates() {
-            let reg_str = datetime_format_to_regex(&validates.format);
-            let format_regex = Regex::new(&reg_str).unwrap();
-            if !format_regex.is_match(&remind.message) {
-                unmatched.insert(name.clone(), validates.clone());
-            }
-        }
+    report_diagnostics(&reminders.diagnostics);
 
-        if !unmatched.is_empty() {
-            invalid_reminds.push(InvalidRemind { remind, unmatched });
+    for remind in reminders.reminds {
+        let failures = validator.validate(&remind);
+        if !failures.is_empty() || remind.invalid_datetime.is_some() {
+            invalid_reminds.push(InvalidRemind { remind, failures });
         }
     }
 
@@ -47,23 +44,44 @@ pub fn execute_validates(command: ValidateCommand) -> Result<(), Error> {
 
         for invalid_remind in &invalid_reminds {
             let remind = &invalid_remind.remind;
-            let unmatched = &invalid_remind.unmatched;
 
             print!(
                 "{}:{} {}",
                 remind.position.file, remind.position.line, remind.message
             );
-            for (name, format) in unmatched {
-                println!(
-                    "Missing `{}` format: \x1b[31m{}\x1b[0m ",
-                    name, format.format
-                );
+            if let Some(invalid) = &remind.invalid_datetime {
+                println!("Invalid datetime: \x1b[31m{}\x1b[0m ", invalid);
+            }
+            for failure in &invalid_remind.failures {
+                print_failure(failure);
             }
             println!();
         }
         std::process::exit(1);
     }
 
+    if command.fail_on_diagnostics && !reminders.diagnostics.is_empty() {
+        pretty_print(
+            format!("found {} diagnostics", reminders.diagnostics.len()),
+            crate::print::Status::Error,
+        );
+        std::process::exit(1);
+    }
+
     pretty_print("all reminders are valid", crate::print::Status::Success);
     Ok(())
 }
+
+fn print_failure(failure: &ValidationFailure) {
+    match (&failure.meta, &failure.found) {
+        (None, _) => println!(
+            "Missing `{}` format: \x1b[31m{}\x1b[0m ",
+            failure.name, failure.expected
+        ),
+        (Some(meta), None) => println!("Missing `${{{}}}` required by `{}`", meta, failure.name),
+        (Some(meta), Some(found)) => println!(
+            "Invalid `${{{}}}` for `{}`: `{}`, expected \x1b[31m{}\x1b[0m ",
+            meta, failure.name, found, failure.expected
+        ),
+    }
+}
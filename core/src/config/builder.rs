@@ -1,33 +1,159 @@
-use crate::remind::meta::contains_meta_matcher;
+use crate::remind::meta::{convert_meta_regex, meta_names};
+use crate::remind::parse_config_datetime;
+use crate::remind::relative::after;
+use crate::remind::trigger::LockfileParsers;
+use crate::remind::LockfileParser;
+use crate::remind::Validator;
 use config::{Config as FileConfigBuilder, ConfigError, Value};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
+use super::timezone::Timezone;
 use super::Config;
 
 pub const DEFAULT_CONFIG_FILE_PATHS: [&str; 2] = ["remind.yml", "remind.yaml"];
 pub const CONFIG_FILE_EXTENSIONS: [&str; 2] = [".yaml", ".yml"];
 pub const DEFAULT_IGNORE_FILE_PATH: &str = ".remindignore";
+pub const DEFAULT_SNOOZE_FILE_PATH: &str = ".remindsnooze";
+pub const DEFAULT_CACHE_DIRECTORY: &str = ".reminder-lint";
+pub const DEFAULT_CACHE_FILE_PATH: &str = ".reminder-lint/cache";
 const REMIND_ENV_PREFIX: &str = "REMIND";
 
 pub struct ConfigBuilder {
     config_file_path: Option<String>,
     ignore_file_path: Option<String>,
+    snooze_file_path: Option<String>,
     sort_by_deadline: Option<bool>,
     remind_if_no_date: Option<bool>,
+    threads: Option<usize>,
+    since: Option<String>,
+    blame: Option<bool>,
+    cache: Option<bool>,
+    lockfile_parsers: LockfileParsers,
 }
 
+/// `datetime_format` is either a single format or a list of formats tried in order.
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum DatetimeFormat {
+    Single(String),
+    List(Vec<String>),
+}
+
+impl DatetimeFormat {
+    pub fn formats(&self) -> &[String] {
+        match self {
+            DatetimeFormat::Single(format) => std::slice::from_ref(format),
+            DatetimeFormat::List(formats) => formats,
+        }
+    }
+}
+
+/// An entry of `validates`, checking a meta field or, without `meta`, the whole message.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct ValidateItem {
+    /// A regex the message must contain, or the meta value must match as a whole. Chrono
+    /// specifiers such as `%Y` match the digits of a date.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub format: String,
+    /// The name of the meta field to check, e.g. `assignee` for `${assignee}`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub meta: String,
+    /// A chrono format the message must contain a valid date in, or the meta value must be.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub date_format: String,
+    /// The values the meta field may take.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed: Vec<String>,
+    /// Whether the meta field must be present. An absent field passes the other checks.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BlockComment {
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CommentSyntax {
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub line: Vec<String>,
+    #[serde(default)]
+    pub block: Vec<BlockComment>,
+    #[serde(default)]
+    pub strings: Vec<String>,
+}
+
+/// Where the states of issues referenced by `when #1234 closed` triggers are looked up.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct IssueTracker {
+    pub provider: TrackerKind,
+    /// `owner/repo` on GitHub, or the project path on GitLab.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub repository: String,
+    /// The API root, for GitHub Enterprise or self-hosted GitLab.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub api_url: String,
+    /// The environment variable holding the API token.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub token_env: String,
+    /// The JSON file mapping issue ids to states, for the `snapshot` provider.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub snapshot: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackerKind {
+    Github,
+    Gitlab,
+    Snapshot,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct FileConfig {
     pub comment_regex: String,
-    pub datetime_format: String,
+    pub datetime_format: DatetimeFormat,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub timezone: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub milestones: HashMap<String, String>,
+    #[serde(default = "calendar_year", skip_serializing_if = "is_calendar_year")]
+    pub fiscal_year_start: u32,
     pub search_directory: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub search_directories: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
     pub remind_if_no_date: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub comment_aware: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comment_syntaxes: Vec<CommentSyntax>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multiline: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub continuation_marker: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub version_source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue_tracker: Option<IssueTracker>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub warn_before: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error_after: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub recurrence_window: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub blame: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cache: bool,
     pub validates: HashMap<String, ValidateItem>,
 }
 
@@ -35,18 +161,56 @@ impl Default for FileConfig {
     fn default() -> Self {
         Self {
             comment_regex: String::from(r"remind:\W?"),
-            datetime_format: "%Y/%m/%d".to_string(),
+            datetime_format: DatetimeFormat::Single("%Y/%m/%d".to_string()),
+            timezone: String::new(),
+            milestones: HashMap::new(),
+            fiscal_year_start: calendar_year(),
             search_directory: ".".to_string(),
+            search_directories: vec![],
+            include: vec![],
+            exclude: vec![],
             remind_if_no_date: false,
+            comment_aware: false,
+            comment_syntaxes: vec![],
+            multiline: false,
+            continuation_marker: String::new(),
+            version_source: String::new(),
+            issue_tracker: None,
+            warn_before: String::new(),
+            error_after: String::new(),
+            recurrence_window: String::new(),
+            blame: false,
+            cache: false,
             validates: HashMap::new(),
         }
     }
 }
 
+fn calendar_year() -> u32 {
+    1
+}
+
+fn is_calendar_year(month: &u32) -> bool {
+    *month == calendar_year()
+}
+
+impl From<DatetimeFormat> for Value {
+    fn from(format: DatetimeFormat) -> Self {
+        match format {
+            DatetimeFormat::Single(format) => format.into(),
+            DatetimeFormat::List(formats) => formats.into(),
+        }
+    }
+}
+
 impl From<ValidateItem> for Value {
     fn from(item: ValidateItem) -> Self {
         let mut map = HashMap::<String, Value>::new();
         map.insert("format".to_string(), item.format.into());
+        map.insert("meta".to_string(), item.meta.into());
+        map.insert("date_format".to_string(), item.date_format.into());
+        map.insert("allowed".to_string(), item.allowed.into());
+        map.insert("required".to_string(), item.required.into());
         Value::from(map)
     }
 }
@@ -56,8 +220,24 @@ fn load_config(filename: &str) -> Result<FileConfig, ConfigError> {
     let settings = FileConfigBuilder::builder()
         .set_default("comment_regex", default.comment_regex)?
         .set_default("datetime_format", default.datetime_format)?
+        .set_default("timezone", default.timezone)?
+        .set_default("milestones", HashMap::<String, Value>::new())?
+        .set_default("fiscal_year_start", default.fiscal_year_start)?
         .set_default("search_directory", default.search_directory)?
+        .set_default("search_directories", default.search_directories)?
+        .set_default("include", default.include)?
+        .set_default("exclude", default.exclude)?
         .set_default("remind_if_no_date", default.remind_if_no_date)?
+        .set_default("comment_aware", default.comment_aware)?
+        .set_default("comment_syntaxes", Vec::<Value>::new())?
+        .set_default("multiline", default.multiline)?
+        .set_default("continuation_marker", default.continuation_marker)?
+        .set_default("version_source", default.version_source)?
+        .set_default("warn_before", default.warn_before)?
+        .set_default("error_after", default.error_after)?
+        .set_default("recurrence_window", default.recurrence_window)?
+        .set_default("blame", default.blame)?
+        .set_default("cache", default.cache)?
         .set_default(
             "validates",
             default
@@ -84,8 +264,14 @@ impl ConfigBuilder {
         Self {
             config_file_path: None,
             ignore_file_path: None,
+            snooze_file_path: None,
             sort_by_deadline: None,
             remind_if_no_date: None,
+            threads: None,
+            since: None,
+            blame: None,
+            cache: None,
+            lockfile_parsers: LockfileParsers::default(),
         }
     }
 
@@ -107,6 +293,11 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn snooze_file_path(mut self, snooze_file_path: Option<String>) -> Self {
+        self.snooze_file_path = snooze_file_path;
+        self
+    }
+
     pub fn sort_by_deadline(mut self, sort_by_deadline: Option<bool>) -> Self {
         self.sort_by_deadline = sort_by_deadline;
         self
@@ -117,6 +308,33 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn threads(mut self, threads: Option<usize>) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    pub fn since(mut self, since: Option<String>) -> Self {
+        self.since = since;
+        self
+    }
+
+    pub fn blame(mut self, blame: Option<bool>) -> Self {
+        self.blame = blame;
+        self
+    }
+
+    pub fn cache(mut self, cache: Option<bool>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Registers a parser for a lockfile `dependency` triggers read, taking precedence over a
+    /// built-in parser for the same file name.
+    pub fn lockfile_parser(mut self, parser: impl LockfileParser + 'static) -> Self {
+        self.lockfile_parsers.push(Arc::new(parser));
+        self
+    }
+
     pub fn build(self) -> Result<Config, ConfigError> {
         let config_file_path = match self.config_file_path {
             Some(path) => {
@@ -137,25 +355,125 @@ impl ConfigBuilder {
         let ignore_file_path = self
             .ignore_file_path
             .unwrap_or(DEFAULT_IGNORE_FILE_PATH.to_string());
+        let snooze_file_path = self
+            .snooze_file_path
+            .unwrap_or(DEFAULT_SNOOZE_FILE_PATH.to_string());
         let file_config = load_config(&config_file_path)?;
         let remind_if_no_date = self
             .remind_if_no_date
             .unwrap_or(file_config.remind_if_no_date);
 
-        if !file_config.validates.is_empty() && contains_meta_matcher(&file_config.comment_regex) {
+        let datetime_formats = file_config.datetime_format.formats().to_vec();
+        if datetime_formats.is_empty() {
             return Err(ConfigError::Message(
-                "Validation and meta matcher features cannot be used together".to_string(),
+                "`datetime_format` must contain at least one format".to_string(),
             ));
         }
 
+        // Dates without an explicit offset are read as UTC unless a timezone is configured.
+        let timezone = match file_config.timezone.as_str() {
+            "" => Timezone::default(),
+            timezone => timezone
+                .parse()
+                .map_err(|e| ConfigError::Message(format!("Invalid timezone: {}", e)))?,
+        };
+
+        convert_meta_regex(&file_config.comment_regex, &datetime_formats)
+            .map_err(|e| ConfigError::Message(format!("Invalid comment_regex: {:#}", e)))?;
+        Validator::new(&file_config.validates)
+            .map_err(|e| ConfigError::Message(format!("Invalid validates: {:#}", e)))?;
+        let names = meta_names(&file_config.comment_regex, &datetime_formats)
+            .map_err(|e| ConfigError::Message(format!("Invalid comment_regex: {:#}", e)))?;
+        let mut validates = file_config.validates.iter().collect::<Vec<_>>();
+        validates.sort_by_key(|(name, _)| *name);
+        for (name, item) in validates {
+            if !item.meta.is_empty() && !names.contains(&item.meta) {
+                return Err(ConfigError::Message(format!(
+                    "Invalid validates: `{}`: `{}` is not a placeholder in comment_regex",
+                    name, item.meta
+                )));
+            }
+        }
+
+        if let Some(tracker) = &file_config.issue_tracker {
+            let required = match tracker.provider {
+                TrackerKind::Github | TrackerKind::Gitlab => ("repository", &tracker.repository),
+                TrackerKind::Snapshot => ("snapshot", &tracker.snapshot),
+            };
+            if required.1.is_empty() {
+                return Err(ConfigError::Message(format!(
+                    "`issue_tracker.{}` is required by this provider",
+                    required.0
+                )));
+            }
+        }
+
+        for (name, duration) in [
+            ("warn_before", &file_config.warn_before),
+            ("error_after", &file_config.error_after),
+            ("recurrence_window", &file_config.recurrence_window),
+        ] {
+            if !duration.is_empty() && after(0, duration).is_none() {
+                return Err(ConfigError::Message(format!(
+                    "Invalid `{}`: expected a duration such as 14d, got `{}`",
+                    name, duration
+                )));
+            }
+        }
+
+        if !(1..=12).contains(&file_config.fiscal_year_start) {
+            return Err(ConfigError::Message(format!(
+                "`fiscal_year_start` must be a month from 1 to 12, got {}",
+                file_config.fiscal_year_start
+            )));
+        }
+        let milestones = file_config
+            .milestones
+            .iter()
+            .map(|(name, date)| {
+                parse_config_datetime(date, &datetime_formats, timezone)
+                    .map(|deadline| (name.to_lowercase(), deadline))
+                    .map_err(|e| {
+                        ConfigError::Message(format!("Invalid milestone `{}`: {}", name, e))
+                    })
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        // `search_directories` takes precedence over the single `search_directory`.
+        let search_directories = if file_config.search_directories.is_empty() {
+            vec![file_config.search_directory]
+        } else {
+            file_config.search_directories
+        };
+
         Ok(Config {
             comment_regex: file_config.comment_regex,
-            datetime_format: file_config.datetime_format,
-            search_directory: file_config.search_directory,
+            datetime_formats,
+            timezone,
+            milestones,
+            fiscal_year_start: file_config.fiscal_year_start,
+            search_directories,
+            include: file_config.include,
+            exclude: file_config.exclude,
             remind_if_no_date,
+            comment_aware: file_config.comment_aware,
+            comment_syntaxes: file_config.comment_syntaxes,
+            multiline: file_config.multiline,
+            continuation_marker: file_config.continuation_marker,
+            version_source: file_config.version_source,
+            issue_tracker: file_config.issue_tracker,
+            warn_before: file_config.warn_before,
+            error_after: file_config.error_after,
+            recurrence_window: file_config.recurrence_window,
             validates: file_config.validates,
             ignore_file_path,
+            snooze_file_path,
             sort_by_deadline: self.sort_by_deadline.unwrap_or(false),
+            threads: self.threads.unwrap_or(0),
+            since: self.since,
+            blame: self.blame.unwrap_or(file_config.blame),
+            cache: self.cache.unwrap_or(file_config.cache),
+            lockfile_parsers: self.lockfile_parsers,
         })
     }
 }
@@ -1,19 +1,44 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use builder::ValidateItem;
+use builder::{CommentSyntax, IssueTracker, ValidateItem};
 use serde::{Deserialize, Serialize};
+use timezone::Timezone;
+
+use crate::remind::trigger::LockfileParsers;
 
 pub mod builder;
+pub mod timezone;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
     comment_regex: String,
-    datetime_format: String,
-    search_directory: String,
+    datetime_formats: Vec<String>,
+    timezone: Timezone,
+    milestones: BTreeMap<String, i64>,
+    fiscal_year_start: u32,
+    search_directories: Vec<String>,
+    include: Vec<String>,
+    exclude: Vec<String>,
     ignore_file_path: String,
+    snooze_file_path: String,
     sort_by_deadline: bool,
     remind_if_no_date: bool,
+    threads: usize,
+    since: Option<String>,
+    blame: bool,
+    cache: bool,
+    comment_aware: bool,
+    comment_syntaxes: Vec<CommentSyntax>,
+    multiline: bool,
+    continuation_marker: String,
+    version_source: String,
+    issue_tracker: Option<IssueTracker>,
+    warn_before: String,
+    error_after: String,
+    recurrence_window: String,
     validates: HashMap<String, ValidateItem>,
+    #[serde(skip)]
+    lockfile_parsers: LockfileParsers,
 }
 
 impl Config {
@@ -21,18 +46,44 @@ impl Config {
         &self.comment_regex
     }
 
-    pub fn datetime_format(&self) -> &str {
-        &self.datetime_format
+    pub fn datetime_formats(&self) -> &[String] {
+        &self.datetime_formats
+    }
+
+    pub fn timezone(&self) -> Timezone {
+        self.timezone
+    }
+
+    /// The deadline of each milestone by lowercase name, as written after `@` in comments.
+    pub fn milestones(&self) -> &BTreeMap<String, i64> {
+        &self.milestones
+    }
+
+    /// The month fiscal years start in, from 1 for January.
+    pub fn fiscal_year_start(&self) -> u32 {
+        self.fiscal_year_start
+    }
+
+    pub fn search_directories(&self) -> &[String] {
+        &self.search_directories
+    }
+
+    pub fn include(&self) -> &[String] {
+        &self.include
     }
 
-    pub fn search_directory(&self) -> &str {
-        &self.search_directory
+    pub fn exclude(&self) -> &[String] {
+        &self.exclude
     }
 
     pub fn ignore_file_path(&self) -> &str {
         &self.ignore_file_path
     }
 
+    pub fn snooze_file_path(&self) -> &str {
+        &self.snooze_file_path
+    }
+
     pub fn sort_by_deadline(&self) -> bool {
         self.sort_by_deadline
     }
@@ -41,7 +92,69 @@ impl Config {
         self.remind_if_no_date
     }
 
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn since(&self) -> Option<&str> {
+        self.since.as_deref()
+    }
+
+    pub fn blame(&self) -> bool {
+        self.blame
+    }
+
+    pub fn cache(&self) -> bool {
+        self.cache
+    }
+
+    pub fn comment_aware(&self) -> bool {
+        self.comment_aware
+    }
+
+    pub fn comment_syntaxes(&self) -> &[CommentSyntax] {
+        &self.comment_syntaxes
+    }
+
+    pub fn multiline(&self) -> bool {
+        self.multiline
+    }
+
+    pub fn continuation_marker(&self) -> &str {
+        &self.continuation_marker
+    }
+
+    /// Where the current version for `version` triggers is read from: a file path or `git_tag`.
+    /// Empty when it is detected automatically.
+    pub fn version_source(&self) -> &str {
+        &self.version_source
+    }
+
+    pub fn issue_tracker(&self) -> Option<&IssueTracker> {
+        self.issue_tracker.as_ref()
+    }
+
+    /// How long before its deadline a reminder is reported as a warning, e.g. `14d`.
+    pub fn warn_before(&self) -> &str {
+        &self.warn_before
+    }
+
+    /// How long after its deadline an expired reminder stays a warning before it is an error.
+    pub fn error_after(&self) -> &str {
+        &self.error_after
+    }
+
+    /// How long a recurring reminder stays due after each occurrence, e.g. `7d`.
+    /// Empty when it stays due until the next occurrence.
+    pub fn recurrence_window(&self) -> &str {
+        &self.recurrence_window
+    }
+
     pub fn validates(&self) -> &HashMap<String, ValidateItem> {
         &self.validates
     }
+
+    pub(crate) fn lockfile_parsers(&self) -> &LockfileParsers {
+        &self.lockfile_parsers
+    }
 }
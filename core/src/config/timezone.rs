@@ -0,0 +1,239 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error};
+use chrono::{FixedOffset, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Abbreviations accepted besides IANA names. Ambiguous ones such as `CST` or `IST` are left out.
+const ABBREVIATIONS: [(&str, i32); 24] = [
+    ("JST", 9),
+    ("KST", 9),
+    ("HKT", 8),
+    ("SGT", 8),
+    ("AEST", 10),
+    ("AEDT", 11),
+    ("NZST", 12),
+    ("NZDT", 13),
+    ("WET", 0),
+    ("WEST", 1),
+    ("CET", 1),
+    ("CEST", 2),
+    ("EET", 2),
+    ("EEST", 3),
+    ("EST", -5),
+    ("EDT", -4),
+    ("CDT", -5),
+    ("MST", -7),
+    ("MDT", -6),
+    ("PST", -8),
+    ("PDT", -7),
+    ("AKST", -9),
+    ("AKDT", -8),
+    ("HST", -10),
+];
+
+/// The zone a date without an explicit offset is read in: an IANA name or a fixed offset.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Timezone {
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        Timezone::Named(Tz::UTC)
+    }
+}
+
+impl Timezone {
+    /// Resolves a wall-clock time in this zone to a Unix timestamp.
+    ///
+    /// A time repeated by a DST change resolves to its first occurrence, and a time skipped by
+    /// one is an error.
+    pub fn timestamp(&self, datetime: NaiveDateTime) -> Result<i64, Error> {
+        let resolved = match self {
+            Timezone::Named(tz) => tz
+                .from_local_datetime(&datetime)
+                .earliest()
+                .map(|d| d.timestamp()),
+            Timezone::Fixed(offset) => offset
+                .from_local_datetime(&datetime)
+                .earliest()
+                .map(|d| d.timestamp()),
+        };
+        resolved.ok_or_else(|| anyhow!("{} does not exist in {}", datetime, self))
+    }
+
+    /// Parses the zone names written next to dates in comments.
+    ///
+    /// Unlike the config, single-word IANA names such as `Japan` or `Poland` are not accepted,
+    /// as they are more likely to be ordinary words than zones.
+    pub(crate) fn from_suffix(s: &str) -> Option<Self> {
+        match s.contains('/') {
+            true => s.parse::<Tz>().ok().map(Timezone::Named),
+            false => match s {
+                "Z" | "UTC" | "GMT" => Some(Timezone::default()),
+                _ => parse_offset(s)
+                    .or_else(|| abbreviation(s))
+                    .map(Timezone::Fixed),
+            },
+        }
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        if let Some(offset) = parse_offset(s) {
+            return Ok(Timezone::Fixed(offset));
+        }
+        // IANA names come first, so `CET` in the config keeps following daylight saving time.
+        s.parse::<Tz>()
+            .map(Timezone::Named)
+            .ok()
+            .or_else(|| abbreviation(s).map(Timezone::Fixed))
+            .ok_or_else(|| anyhow!("unknown timezone `{}`", s))
+    }
+}
+
+impl TryFrom<String> for Timezone {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Error> {
+        s.parse()
+    }
+}
+
+impl From<Timezone> for String {
+    fn from(timezone: Timezone) -> Self {
+        timezone.to_string()
+    }
+}
+
+impl Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timezone::Named(tz) => f.write_str(tz.name()),
+            Timezone::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+/// Parses `+09:00`, `+0900`, `+930`, `+09` or `-5`, optionally prefixed with `UTC` or `GMT`.
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    let s = s
+        .strip_prefix("UTC")
+        .or_else(|| s.strip_prefix("GMT"))
+        .unwrap_or(s);
+    let (sign, rest) = match s.chars().next()? {
+        '+' => (1, &s[1..]),
+        '-' => (-1, &s[1..]),
+        '−' => (-1, &s['−'.len_utf8()..]),
+        _ => return None,
+    };
+    if rest.is_empty() || !rest.chars().all(|c| c.is_ascii_digit() || c == ':') {
+        return None;
+    }
+
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if rest.len() > 2 => rest.split_at(rest.len() - 2),
+        None => (rest, "0"),
+    };
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+fn abbreviation(s: &str) -> Option<FixedOffset> {
+    ABBREVIATIONS
+        .iter()
+        .find(|(name, _)| *name == s)
+        .and_then(|(_, hours)| FixedOffset::east_opt(hours * 3600))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    fn midnight(year: i32, month: u32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        let tokyo = "Asia/Tokyo".parse::<Timezone>().unwrap();
+        assert_eq!(tokyo.timestamp(midnight(2024, 6, 27)).unwrap(), 1719414000);
+
+        for offset in ["+09:00", "+0900", "+09", "UTC+9", "JST"] {
+            let timezone = offset.parse::<Timezone>().unwrap();
+            assert_eq!(
+                timezone.timestamp(midnight(2024, 6, 27)).unwrap(),
+                1719414000
+            );
+        }
+
+        assert_eq!("UTC".parse::<Timezone>().unwrap(), Timezone::default());
+        assert!("Mars/Olympus_Mons".parse::<Timezone>().is_err());
+    }
+
+    #[test]
+    fn test_timezone_dst() {
+        let new_york = "America/New_York".parse::<Timezone>().unwrap();
+        assert_eq!(
+            new_york.timestamp(midnight(2024, 1, 15)).unwrap(),
+            1705294800
+        );
+        assert_eq!(
+            new_york.timestamp(midnight(2024, 7, 15)).unwrap(),
+            1721016000
+        );
+
+        let gap = NaiveDate::from_ymd_opt(2024, 3, 10)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        assert!(new_york.timestamp(gap).is_err());
+    }
+
+    #[test]
+    fn test_timezone_from_suffix() {
+        assert!(Timezone::from_suffix("Asia/Tokyo").is_some());
+        assert!(Timezone::from_suffix("+09:00").is_some());
+        assert!(Timezone::from_suffix("JST").is_some());
+        assert_eq!(
+            Timezone::from_suffix("GMT+9"),
+            FixedOffset::east_opt(9 * 3600).map(Timezone::Fixed)
+        );
+        assert_eq!(
+            Timezone::from_suffix("UTC+530"),
+            FixedOffset::east_opt(5 * 3600 + 30 * 60).map(Timezone::Fixed)
+        );
+        assert!(Timezone::from_suffix("GMT+25").is_none());
+        for (suffix, hours) in [("EST", -5), ("MST", -7), ("HST", -10), ("CET", 1)] {
+            assert_eq!(
+                Timezone::from_suffix(suffix),
+                FixedOffset::east_opt(hours * 3600).map(Timezone::Fixed),
+                "{}",
+                suffix
+            );
+        }
+        // `CST` is Central, China or Cuba Standard Time.
+        assert!(Timezone::from_suffix("CST").is_none());
+        assert!(Timezone::from_suffix("Poland").is_none());
+        assert!(Timezone::from_suffix("TODO").is_none());
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::{BTreeMap, HashMap};
+
+use git2::{BlameOptions, Oid, Repository};
+
+use super::Repo;
+use crate::remind::{Blame, Remind};
+
+/// Attributes every reminder to the commit that last touched its line.
+///
+/// The blame runs against the file as it is on disk, so lines that are not committed yet
+/// and files outside the repository are left without attribution.
+pub(crate) fn blame(repo: &Repo, reminds: &mut [Remind]) {
+    let mut commit_times = HashMap::new();
+
+    let mut by_file: BTreeMap<String, Vec<&mut Remind>> = BTreeMap::new();
+    for remind in reminds.iter_mut() {
+        by_file
+            .entry(remind.position.file.clone())
+            .or_default()
+            .push(remind);
+    }
+
+    for (file, reminds) in by_file {
+        blame_on_disk(repo, &file, |current| {
+            for remind in reminds {
+                let Some(hunk) = current.get_line(remind.position.line as usize) else {
+                    continue;
+                };
+                let commit_id = hunk.final_commit_id();
+                if commit_id.is_zero() {
+                    continue;
+                }
+
+                let signature = hunk.final_signature();
+                remind.attribute(Blame {
+                    author_name: signature.name().unwrap_or_default().to_string(),
+                    author_email: signature.email().unwrap_or_default().to_string(),
+                    commit_id: commit_id.to_string(),
+                    commit_time: commit_time(&repo.repository, commit_id, &mut commit_times),
+                });
+            }
+        });
+    }
+}
+
+/// The time of the commit that last touched each of `lines` in `file`.
+/// Lines that are not committed yet are left out.
+pub(crate) fn line_commit_times(repo: &Repo, file: &str, lines: &[u64]) -> HashMap<u64, i64> {
+    let mut commit_times = HashMap::new();
+
+    blame_on_disk(repo, file, |current| {
+        lines
+            .iter()
+            .filter_map(|&line| {
+                let commit_id = current.get_line(line as usize)?.final_commit_id();
+                match commit_id.is_zero() {
+                    true => None,
+                    false => Some((
+                        line,
+                        commit_time(&repo.repository, commit_id, &mut commit_times),
+                    )),
+                }
+            })
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Blames the committed history of `file`, then its content on disk on top of it.
+fn blame_on_disk<T>(repo: &Repo, file: &str, f: impl FnOnce(&git2::Blame) -> T) -> Option<T> {
+    let path = repo.relative_path(file)?;
+    let committed = repo
+        .repository
+        .blame_file(&path, Some(&mut BlameOptions::new()))
+        .ok()?;
+    let content = std::fs::read(file).ok()?;
+    let current = committed.blame_buffer(&content).ok()?;
+    Some(f(&current))
+}
+
+fn commit_time(repository: &Repository, id: Oid, cache: &mut HashMap<Oid, i64>) -> i64 {
+    *cache.entry(id).or_insert_with(|| {
+        repository
+            .find_commit(id)
+            .map_or(0, |commit| commit.time().seconds())
+    })
+}
@@ -0,0 +1,114 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use anyhow::Error;
+use git2::{DiffOptions, Repository};
+
+use super::{relative_path, Repo};
+
+/// Lines added or modified in the work tree since the merge base with a base revision.
+pub(crate) struct ChangedLines {
+    workdir: PathBuf,
+    files: HashMap<PathBuf, HashSet<u64>>,
+}
+
+impl ChangedLines {
+    pub(crate) fn since(base: &str) -> Result<Self, Error> {
+        let repo = Repo::discover()?;
+        let files = changed_lines(&repo.repository, base)?;
+
+        Ok(Self {
+            workdir: repo.workdir().to_path_buf(),
+            files,
+        })
+    }
+
+    /// Reports whether any line from `start` to `end` (inclusive) of `file` was changed.
+    pub(crate) fn contains(&self, file: &str, start: u64, end: u64) -> bool {
+        relative_path(&self.workdir, file)
+            .and_then(|path| self.files.get(&path))
+            .is_some_and(|lines| (start..=end).any(|line| lines.contains(&line)))
+    }
+}
+
+/// Diffs the work tree (including staged and untracked files) against the merge base of
+/// `base` and `HEAD`, so only changes introduced on the current branch are reported.
+fn changed_lines(
+    repository: &Repository,
+    base: &str,
+) -> Result<HashMap<PathBuf, HashSet<u64>>, Error> {
+    let base = repository.revparse_single(base)?.peel_to_commit()?;
+    let merge_base = match repository.head().and_then(|h| h.peel_to_commit()) {
+        Ok(head) => repository
+            .merge_base(base.id(), head.id())
+            .unwrap_or(base.id()),
+        Err(_) => base.id(),
+    };
+    let tree = repository.find_commit(merge_base)?.tree()?;
+
+    let mut options = DiffOptions::new();
+    options
+        .context_lines(0)
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    let diff = repository.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+
+    let mut files: HashMap<PathBuf, HashSet<u64>> = HashMap::new();
+    diff.foreach(
+        &mut |_, _| true,
+        None,
+        None,
+        Some(&mut |delta, _, line| {
+            if let (Some(path), Some(line_num), '+') =
+                (delta.new_file().path(), line.new_lineno(), line.origin())
+            {
+                files
+                    .entry(path.to_path_buf())
+                    .or_default()
+                    .insert(line_num as u64);
+            }
+            true
+        }),
+    )?;
+
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{IndexAddOption, Signature};
+    use std::fs;
+
+    #[test]
+    fn test_changed_lines_since_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repository = Repository::init(root).unwrap();
+
+        fs::write(root.join("old.rs"), "// remind: 2020/01/01 a\nfn f() {}\n").unwrap();
+        let mut index = repository.index().unwrap();
+        index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("alice", "alice@example.com").unwrap();
+        repository
+            .commit(Some("HEAD"), &signature, &signature, "base", &tree, &[])
+            .unwrap();
+
+        // An edited line and an untracked file both count as changed.
+        fs::write(root.join("old.rs"), "// remind: 2020/01/01 a\nfn g() {}\n").unwrap();
+        fs::write(root.join("new.rs"), "// remind: 2020/01/01 b\n").unwrap();
+
+        let changed = ChangedLines {
+            workdir: root.canonicalize().unwrap(),
+            files: changed_lines(&repository, "HEAD").unwrap(),
+        };
+        let old = root.join("old.rs").display().to_string();
+        let new = root.join("new.rs").display().to_string();
+        assert!(!changed.contains(&old, 1, 1));
+        assert!(changed.contains(&old, 2, 2));
+        assert!(changed.contains(&old, 1, 2));
+        assert!(changed.contains(&new, 1, 1));
+    }
+}
@@ -0,0 +1,50 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Error;
+use git2::{DescribeFormatOptions, DescribeOptions, Repository};
+
+pub(crate) mod blame;
+pub(crate) mod diff;
+
+/// A repository discovered from the working directory, with paths resolved against its work tree.
+pub(crate) struct Repo {
+    repository: Repository,
+    workdir: PathBuf,
+}
+
+impl Repo {
+    pub(crate) fn discover() -> Result<Self, Error> {
+        let repository = Repository::discover(".")?;
+        let workdir = repository
+            .workdir()
+            .ok_or_else(|| Error::msg("bare repositories are not supported"))?
+            .canonicalize()?;
+
+        Ok(Self {
+            repository,
+            workdir,
+        })
+    }
+
+    pub(crate) fn workdir(&self) -> &Path {
+        &self.workdir
+    }
+
+    pub(crate) fn relative_path(&self, file: &str) -> Option<PathBuf> {
+        relative_path(&self.workdir, file)
+    }
+
+    /// The name of the nearest tag reachable from `HEAD`.
+    pub(crate) fn nearest_tag(&self) -> Result<String, Error> {
+        let describe = self
+            .repository
+            .describe(DescribeOptions::new().describe_tags())?;
+        Ok(describe.format(Some(DescribeFormatOptions::new().abbreviated_size(0)))?)
+    }
+}
+
+/// Converts a path as reported in `Position::file` into a path relative to the work tree.
+pub(crate) fn relative_path(workdir: &Path, file: &str) -> Option<PathBuf> {
+    let absolute = Path::new(file).canonicalize().ok()?;
+    absolute.strip_prefix(workdir).ok().map(Path::to_path_buf)
+}
@@ -1,5 +1,6 @@
 pub mod config;
 pub mod error;
+mod git;
 pub mod remind;
 
 use config::Config;
@@ -10,10 +11,23 @@ use serde::Serialize;
 #[derive(Debug, Serialize)]
 pub struct Reminders {
     pub reminds: Vec<remind::Remind>,
+    pub diagnostics: Vec<remind::Diagnostic>,
+}
+
+/// Streams reminders as they are found, with progress events and cancellation.
+/// `reminders` collects the same stream and sorts it.
+pub fn stream(conf: &Config) -> Result<remind::ReminderStream, ReminderLintError> {
+    remind::stream_reminders(conf).map_err(ReminderLintError::from)
 }
 
 pub fn reminders(conf: &Config) -> Result<Reminders, ReminderLintError> {
-    let reminders = list_reminders(conf).map_err(ReminderLintError::from)?;
+    list_reminders(conf).map_err(ReminderLintError::from)
+}
 
-    Ok(Reminders { reminds: reminders })
+pub fn scan_text(
+    conf: &Config,
+    virtual_path: &str,
+    text: &str,
+) -> Result<Reminders, ReminderLintError> {
+    remind::scan_text(conf, virtual_path, text).map_err(ReminderLintError::from)
 }
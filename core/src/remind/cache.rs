@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+use super::hash::fnv1a;
+use super::scanner::FileScan;
+use crate::config::builder::DEFAULT_CACHE_FILE_PATH;
+use crate::config::Config;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    key: String,
+    files: HashMap<String, Entry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    size: u64,
+    mtime: u128,
+    hash: u64,
+    scan: FileScan,
+}
+
+/// What was extracted from each file on a previous run, reused while the file is unchanged.
+///
+/// A file is unchanged when its size and mtime match, or, failing that, when its content hash does.
+/// The whole cache is discarded when any setting that affects extraction changes.
+pub(crate) struct Cache {
+    path: PathBuf,
+    key: String,
+    previous: HashMap<String, Entry>,
+    current: Mutex<HashMap<String, Entry>>,
+}
+
+impl Cache {
+    pub(crate) fn load(config: &Config) -> Self {
+        Self::open(PathBuf::from(DEFAULT_CACHE_FILE_PATH), cache_key(config))
+    }
+
+    pub(crate) fn open(path: PathBuf, key: String) -> Self {
+        let previous = fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<CacheFile>(&bytes).ok())
+            .filter(|cache| cache.key == key)
+            .map(|cache| cache.files)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            key,
+            previous,
+            current: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn get_or_scan(&self, path: &Path, scan: impl FnOnce() -> FileScan) -> FileScan {
+        let Ok(metadata) = fs::metadata(path) else {
+            return scan();
+        };
+        let file = path.display().to_string();
+        let size = metadata.len();
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos());
+
+        let cached = self.previous.get(&file);
+        let entry = match cached {
+            Some(entry) if entry.size == size && entry.mtime == mtime => entry.clone(),
+            _ => {
+                let Ok(content) = fs::read(path) else {
+                    return scan();
+                };
+                let hash = fnv1a(&content);
+                match cached {
+                    Some(entry) if entry.size == size && entry.hash == hash => Entry {
+                        mtime,
+                        ..entry.clone()
+                    },
+                    _ => Entry {
+                        size,
+                        mtime,
+                        hash,
+                        scan: scan(),
+                    },
+                }
+            }
+        };
+
+        let scan = entry.scan.clone();
+        self.current.lock().unwrap().insert(file, entry);
+        scan
+    }
+
+    /// Writes the entries of the files visited in this run, dropping the rest.
+    pub(crate) fn save(self) -> Result<(), Error> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let cache = CacheFile {
+            key: self.key,
+            files: self.current.into_inner().unwrap(),
+        };
+        // Written next to the cache and renamed over it, so that an interrupted run or a run in
+        // parallel never leaves a half-written cache behind.
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(format!(".{}.tmp", std::process::id()));
+        fs::write(&temporary, serde_json::to_vec(&cache)?)?;
+        if let Err(e) = fs::rename(&temporary, &self.path) {
+            let _ = fs::remove_file(&temporary);
+            return Err(e.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Everything that changes what is extracted from a file, so a change invalidates the cache.
+fn cache_key(config: &Config) -> String {
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "comment_regex": config.comment_regex(),
+        "datetime_format": config.datetime_formats(),
+        "timezone": config.timezone(),
+        "milestones": config.milestones(),
+        "fiscal_year_start": config.fiscal_year_start(),
+        "comment_aware": config.comment_aware(),
+        "comment_syntaxes": config.comment_syntaxes(),
+        "multiline": config.multiline(),
+        "continuation_marker": config.continuation_marker(),
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::builder::ConfigBuilder;
+    use crate::remind::scan_text;
+    use pretty_assertions::assert_eq;
+    use std::cell::Cell;
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+
+    /// Scans `path` through the cache, returning whether the file was searched again.
+    fn scanned(cache: &Cache, path: &Path) -> bool {
+        let searched = Cell::new(false);
+        cache.get_or_scan(path, || {
+            searched.set(true);
+            FileScan::default()
+        });
+        searched.get()
+    }
+
+    fn set_mtime(path: &Path, secs: u64) {
+        let mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_cache_hits_and_misses() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("cache.json");
+        let file = dir.path().join("main.rs");
+        fs::write(&file, "// remind: 2024/06/27 a\n").unwrap();
+        set_mtime(&file, 1_000);
+
+        let cache = Cache::open(cache_path.clone(), "key".into());
+        assert!(scanned(&cache, &file));
+        cache.save().unwrap();
+
+        // Unchanged size and mtime.
+        let cache = Cache::open(cache_path.clone(), "key".into());
+        assert!(!scanned(&cache, &file));
+        cache.save().unwrap();
+
+        // Touched, but the content hash still matches.
+        set_mtime(&file, 2_000);
+        let cache = Cache::open(cache_path.clone(), "key".into());
+        assert!(!scanned(&cache, &file));
+        cache.save().unwrap();
+
+        // Same size, different content.
+        fs::write(&file, "// remind: 2024/06/28 a\n").unwrap();
+        set_mtime(&file, 3_000);
+        let cache = Cache::open(cache_path.clone(), "key".into());
+        assert!(scanned(&cache, &file));
+        cache.save().unwrap();
+
+        // A setting that affects extraction changed.
+        let cache = Cache::open(cache_path, "other key".into());
+        assert!(scanned(&cache, &file));
+    }
+
+    #[test]
+    fn test_cache_key_invalidation() {
+        let config = ConfigBuilder::new().build().unwrap();
+        assert_eq!(cache_key(&config), cache_key(&config));
+
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("remind.yml");
+        fs::write(&config_path, "comment_regex: \"todo:\"\n").unwrap();
+        let changed = ConfigBuilder::new()
+            .config_file_path(Some(config_path.to_string_lossy().into_owned()))
+            .build()
+            .unwrap();
+        assert!(cache_key(&config) != cache_key(&changed));
+    }
+
+    #[test]
+    fn test_cached_scan_round_trips() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let scan = scan_text(&config, "src/main.rs", "// remind: 2024/06/27 a\n").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("cache.json");
+        let file = dir.path().join("main.rs");
+        fs::write(&file, "// remind: 2024/06/27 a\n").unwrap();
+
+        let found = FileScan {
+            reminds: scan.reminds,
+            diagnostics: scan.diagnostics,
+        };
+        let cache = Cache::open(cache_path.clone(), "key".into());
+        cache.get_or_scan(&file, || found.clone());
+        cache.save().unwrap();
+
+        let cache = Cache::open(cache_path, "key".into());
+        let cached = cache.get_or_scan(&file, FileScan::default);
+        assert_eq!(cached.reminds[0].datetime, 1719446400);
+    }
+
+    #[test]
+    fn test_save_replaces_the_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("cache");
+        fs::write(&cache_path, "left by an older build").unwrap();
+
+        Cache::open(cache_path.clone(), "key".into())
+            .save()
+            .unwrap();
+
+        let names = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["cache"]);
+        let saved: CacheFile = serde_json::from_slice(&fs::read(&cache_path).unwrap()).unwrap();
+        assert_eq!(saved.key, "key");
+    }
+}
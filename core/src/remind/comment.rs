@@ -0,0 +1,322 @@
+use std::ops::Range;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::config::builder::{BlockComment, CommentSyntax};
+
+// (extensions or file names, line comments, block comments, string delimiters)
+type BuiltinSyntax = (
+    &'static [&'static str],
+    &'static [&'static str],
+    &'static [(&'static str, &'static str)],
+    &'static [&'static str],
+);
+
+const C_BLOCK: &[(&str, &str)] = &[("/*", "*/")];
+
+const BUILTIN_SYNTAXES: &[BuiltinSyntax] = &[
+    (&["rs"], &["//"], C_BLOCK, &["\""]),
+    (
+        &[
+            "c", "h", "cc", "cpp", "cxx", "hpp", "cs", "java", "kt", "kts", "scala", "swift",
+            "dart", "proto", "groovy", "gradle",
+        ],
+        &["//"],
+        C_BLOCK,
+        &["\"", "'"],
+    ),
+    (&["go"], &["//"], C_BLOCK, &["\"", "'", "`"]),
+    (
+        &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"],
+        &["//"],
+        C_BLOCK,
+        &["\"", "'", "`"],
+    ),
+    (&["php"], &["//", "#"], C_BLOCK, &["\"", "'"]),
+    (&["css", "less"], &[], C_BLOCK, &["\"", "'"]),
+    (&["scss", "sass"], &["//"], C_BLOCK, &["\"", "'"]),
+    (
+        &["py", "pyi"],
+        &["#"],
+        &[("\"\"\"", "\"\"\""), ("'''", "'''")],
+        &["\"", "'"],
+    ),
+    (&["rb"], &["#"], &[("=begin", "=end")], &["\"", "'"]),
+    (
+        &[
+            "sh",
+            "bash",
+            "zsh",
+            "fish",
+            "pl",
+            "pm",
+            "r",
+            "cmake",
+            "mk",
+            "Makefile",
+            "Dockerfile",
+            "nix",
+            "ex",
+            "exs",
+        ],
+        &["#"],
+        &[],
+        &["\"", "'"],
+    ),
+    (
+        &["yml", "yaml", "toml", "conf", "cfg", "properties"],
+        &["#"],
+        &[],
+        &["\""],
+    ),
+    (&["ini"], &[";", "#"], &[], &["\""]),
+    (&["tf", "hcl"], &["#", "//"], C_BLOCK, &["\""]),
+    (&["ps1", "psm1"], &["#"], &[("<#", "#>")], &["\"", "'"]),
+    (&["sql"], &["--"], C_BLOCK, &["'"]),
+    (&["lua"], &["--"], &[("--[[", "]]")], &["\"", "'"]),
+    (&["hs"], &["--"], &[("{-", "-}")], &["\""]),
+    (&["erl", "hrl", "tex"], &["%"], &[], &["\""]),
+    (&["clj", "cljs", "el", "lisp", "scm"], &[";"], &[], &["\""]),
+    (
+        &["html", "htm", "xml", "svg", "md", "markdown"],
+        &[],
+        &[("<!--", "-->")],
+        &[],
+    ),
+    (
+        &["vue", "svelte"],
+        &["//"],
+        &[("/*", "*/"), ("<!--", "-->")],
+        &["\"", "'", "`"],
+    ),
+];
+
+fn builtin_syntax(key: &str) -> Option<CommentSyntax> {
+    let to_strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    BUILTIN_SYNTAXES
+        .iter()
+        .find(|(keys, ..)| keys.contains(&key))
+        .map(|(keys, line, block, strings)| CommentSyntax {
+            extensions: to_strings(keys),
+            line: to_strings(line),
+            block: block
+                .iter()
+                .map(|(start, end)| BlockComment {
+                    start: start.to_string(),
+                    end: end.to_string(),
+                })
+                .collect(),
+            strings: to_strings(strings),
+        })
+}
+
+/// Finds the comment syntax for a path, looking at the file name first and then the extension.
+/// User-defined syntaxes take precedence over the built-in table.
+pub(crate) fn syntax_for(path: &Path, custom: &[CommentSyntax]) -> Option<CommentSyntax> {
+    let keys = [
+        path.file_name().and_then(|n| n.to_str()),
+        path.extension().and_then(|e| e.to_str()),
+    ];
+
+    keys.into_iter().flatten().find_map(|key| {
+        custom
+            .iter()
+            .find(|syntax| syntax.extensions.iter().any(|e| e == key))
+            .cloned()
+            .or_else(|| builtin_syntax(key))
+    })
+}
+
+enum State<'a> {
+    Code,
+    LineComment,
+    BlockComment(&'a str),
+    String(&'a str),
+}
+
+/// Returns the byte ranges of all comments in `text`, markers included.
+///
+/// String literals are skipped so comment markers inside them are not mistaken for comments.
+/// Strings end at an unescaped newline, which keeps a stray quote from swallowing the rest of the file.
+pub(crate) fn comment_ranges(text: &str, syntax: &CommentSyntax) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut state = State::Code;
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < text.len() {
+        let rest = &text[i..];
+        match state {
+            State::Code => {
+                // Prefer the longest token so `"""` wins over `"` and `--[[` over `--`.
+                let line = syntax
+                    .line
+                    .iter()
+                    .filter(|m| rest.starts_with(m.as_str()))
+                    .map(|m| (m.len(), State::LineComment));
+                let block = syntax
+                    .block
+                    .iter()
+                    .filter(|b| rest.starts_with(&b.start))
+                    .map(|b| (b.start.len(), State::BlockComment(&b.end)));
+                let string = syntax
+                    .strings
+                    .iter()
+                    .filter(|d| rest.starts_with(d.as_str()))
+                    .map(|d| (d.len(), State::String(d)));
+                let token = line.chain(block).chain(string).reduce(|longest, t| {
+                    if t.0 > longest.0 {
+                        t
+                    } else {
+                        longest
+                    }
+                });
+
+                match token {
+                    Some((len, next)) => {
+                        if matches!(next, State::LineComment | State::BlockComment(_)) {
+                            start = i;
+                        }
+                        state = next;
+                        i += len;
+                    }
+                    None => i += rest.chars().next().map_or(1, char::len_utf8),
+                }
+            }
+            State::LineComment => match rest.find('\n') {
+                Some(offset) => {
+                    ranges.push(start..i + offset);
+                    state = State::Code;
+                    i += offset + 1;
+                }
+                None => {
+                    ranges.push(start..text.len());
+                    i = text.len();
+                }
+            },
+            State::BlockComment(end) => match rest.find(end) {
+                Some(offset) => {
+                    ranges.push(start..i + offset + end.len());
+                    state = State::Code;
+                    i += offset + end.len();
+                }
+                None => {
+                    ranges.push(start..text.len());
+                    i = text.len();
+                }
+            },
+            State::String(delim) => {
+                if let Some(escaped) = rest.strip_prefix('\\') {
+                    i += 1 + escaped.chars().next().map_or(0, char::len_utf8);
+                } else if rest.starts_with(delim) {
+                    state = State::Code;
+                    i += delim.len();
+                } else if rest.starts_with('\n') && delim.len() == 1 && delim != "`" {
+                    state = State::Code;
+                    i += 1;
+                } else {
+                    i += rest.chars().next().map_or(1, char::len_utf8);
+                }
+            }
+        }
+    }
+
+    ranges
+}
+
+/// Finds the first match of `regex` in `line` at a position covered by one of `ranges`,
+/// returning where it starts in `line`. `line_start` is the byte offset of `line` in the text
+/// the ranges were computed from.
+pub(crate) fn match_in_comment(
+    regex: &Regex,
+    line: &str,
+    line_start: usize,
+    ranges: &[Range<usize>],
+) -> Option<usize> {
+    regex.find_iter(line).map(|m| m.start()).find(|start| {
+        let offset = line_start + start;
+        ranges.iter().any(|r| r.contains(&offset))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn comments<'a>(text: &'a str, extension: &str) -> Vec<&'a str> {
+        let syntax = builtin_syntax(extension).unwrap();
+        comment_ranges(text, &syntax)
+            .into_iter()
+            .map(|r| &text[r])
+            .collect()
+    }
+
+    #[test]
+    fn test_line_and_block_comments() {
+        let text = "let a = 1; // remind: a\n/* remind:\n b */ let b = 2;\n";
+        assert_eq!(
+            comments(text, "rs"),
+            vec!["// remind: a", "/* remind:\n b */"]
+        );
+    }
+
+    #[test]
+    fn test_markers_inside_strings_are_ignored() {
+        let text = "let s = \"// remind: 2020/01/01\"; // real\n";
+        assert_eq!(comments(text, "rs"), vec!["// real"]);
+    }
+
+    #[test]
+    fn test_escaped_quotes_in_strings() {
+        let text = "s = \"\\\" # not a comment\" # comment\n";
+        assert_eq!(comments(text, "py"), vec!["# comment"]);
+    }
+
+    #[test]
+    fn test_docstrings_are_comments() {
+        let text = "def f():\n    \"\"\"remind: 2020/01/01\"\"\"\n    return \"x\"\n";
+        assert_eq!(comments(text, "py"), vec!["\"\"\"remind: 2020/01/01\"\"\""]);
+    }
+
+    #[test]
+    fn test_unterminated_string_stops_at_newline() {
+        let text = "echo don't\n# remind: 2020/01/01\n";
+        assert_eq!(comments(text, "sh"), vec!["# remind: 2020/01/01"]);
+    }
+
+    #[test]
+    fn test_syntax_for_prefers_custom_syntax() {
+        let custom = vec![CommentSyntax {
+            extensions: vec!["rs".to_string()],
+            line: vec!["#".to_string()],
+            block: vec![],
+            strings: vec![],
+        }];
+        let syntax = syntax_for(Path::new("src/main.rs"), &custom).unwrap();
+        assert_eq!(syntax.line, vec!["#".to_string()]);
+
+        let syntax = syntax_for(Path::new("docker/Dockerfile"), &[]).unwrap();
+        assert_eq!(syntax.line, vec!["#".to_string()]);
+
+        assert!(syntax_for(Path::new("notes.txt"), &[]).is_none());
+    }
+
+    #[test]
+    fn test_matches_in_comment() {
+        let regex = Regex::new(r"remind:\W?").unwrap();
+        let text = "x = \"remind: 2020/01/01\"\ny = 1 # remind: 2020/01/01\n";
+        let ranges = comment_ranges(text, &builtin_syntax("py").unwrap());
+
+        assert_eq!(
+            match_in_comment(&regex, "x = \"remind: 2020/01/01\"", 0, &ranges),
+            None
+        );
+        assert_eq!(
+            match_in_comment(&regex, "y = 1 # remind: 2020/01/01", 25, &ranges),
+            Some(8)
+        );
+    }
+}
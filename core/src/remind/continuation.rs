@@ -0,0 +1,184 @@
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::config::builder::CommentSyntax;
+
+/// Where the reminder comment on the matched line lives, which decides what a continuation looks like.
+enum Context<'a> {
+    /// A block comment that keeps going until `end` (a byte offset into the text).
+    Block { end: usize, marker: &'a str },
+    /// A line comment, continued by lines that start with the same `prefix`.
+    Line { prefix: String },
+    /// No comment around the match, so only the continuation marker can extend it.
+    Plain,
+}
+
+/// Collects the continuation text that follows the reminder on line `line_num` (1-based).
+///
+/// `lines` holds every line of the file with its byte offset. A following line continues the
+/// reminder when it belongs to the same comment block (`multiline`) or starts with `marker`.
+/// It stops at blank comment lines, at code, and at a line that is itself a reminder.
+pub(crate) fn continuation_lines(
+    line_num: u64,
+    lines: &[(usize, &str)],
+    ranges: Option<&[Range<usize>]>,
+    syntax: Option<&CommentSyntax>,
+    comment_regex: &Regex,
+    multiline: bool,
+    marker: &str,
+) -> Vec<String> {
+    let index = line_num as usize - 1;
+    let Some((start, line)) = lines.get(index) else {
+        return vec![];
+    };
+    let context = context(*start, line, ranges, syntax, comment_regex);
+
+    let mut continued = vec![];
+    for (offset, line) in &lines[index + 1..] {
+        if comment_regex.is_match(line) {
+            break;
+        }
+
+        let trimmed = line.trim();
+        let (content, in_comment) = match &context {
+            Context::Block { end, marker } if offset < end => {
+                // Drop the closing marker and the `*` gutter of doc-style blocks.
+                let content = trimmed.strip_suffix(marker).unwrap_or(trimmed).trim_end();
+                (content.strip_prefix('*').unwrap_or(content).trim(), true)
+            }
+            Context::Line { prefix } if trimmed.starts_with(prefix.as_str()) => {
+                (trimmed[prefix.len()..].trim(), true)
+            }
+            _ => (trimmed, false),
+        };
+
+        if content.is_empty() {
+            break;
+        }
+
+        match content.strip_prefix(marker).filter(|_| !marker.is_empty()) {
+            Some(rest) => continued.push(rest.trim().to_string()),
+            None if multiline && in_comment => continued.push(content.to_string()),
+            None => break,
+        }
+    }
+
+    continued
+}
+
+fn context<'a>(
+    line_start: usize,
+    line: &str,
+    ranges: Option<&[Range<usize>]>,
+    syntax: Option<&'a CommentSyntax>,
+    comment_regex: &Regex,
+) -> Context<'a> {
+    if let (Some(ranges), Some(syntax)) = (ranges, syntax) {
+        let line_end = line_start + line.trim_end_matches(['\r', '\n']).len();
+        let range = comment_regex.find_iter(line).find_map(|m| {
+            let offset = line_start + m.start();
+            ranges.iter().find(|r| r.contains(&offset))
+        });
+        let Some(range) = range else {
+            return Context::Plain;
+        };
+
+        let opening = &line[range.start.saturating_sub(line_start)..];
+        if let Some(block) = syntax.block.iter().find(|b| opening.starts_with(&b.start)) {
+            if range.end > line_end {
+                return Context::Block {
+                    end: range.end,
+                    marker: &block.end,
+                };
+            }
+            return Context::Plain;
+        }
+
+        return syntax
+            .line
+            .iter()
+            .filter(|m| opening.starts_with(m.as_str()))
+            .max_by_key(|m| m.len())
+            .map_or(Context::Plain, |m| Context::Line { prefix: m.clone() });
+    }
+
+    // Without a known syntax, treat the leading run of punctuation (`//`, `#`, `--`, ...) as the comment marker.
+    let prefix = line
+        .trim_start()
+        .chars()
+        .take_while(|c| !c.is_alphanumeric() && !c.is_whitespace())
+        .collect::<String>();
+    if prefix.is_empty() {
+        Context::Plain
+    } else {
+        Context::Line { prefix }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::builder::BlockComment;
+    use crate::remind::scanner::lines_with_offsets as lines;
+    use pretty_assertions::assert_eq;
+
+    fn rust_syntax() -> CommentSyntax {
+        CommentSyntax {
+            extensions: vec!["rs".to_string()],
+            line: vec!["//".to_string()],
+            block: vec![BlockComment {
+                start: "/*".to_string(),
+                end: "*/".to_string(),
+            }],
+            strings: vec!["\"".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_line_comment_block() {
+        let text = "// remind: 2024/01/01 drop this\n// once the API\n// is stable\n\n// other\n";
+        let regex = Regex::new(r"remind:\W?").unwrap();
+        let continued = continuation_lines(1, &lines(text), None, None, &regex, true, "");
+        assert_eq!(continued, vec!["once the API", "is stable"]);
+    }
+
+    #[test]
+    fn test_stops_at_next_reminder_and_blank_comment() {
+        let text = "# remind: a\n# more\n# remind: b\n# c\n";
+        let regex = Regex::new(r"remind:\W?").unwrap();
+        assert_eq!(
+            continuation_lines(1, &lines(text), None, None, &regex, true, ""),
+            vec!["more"]
+        );
+
+        let text = "# remind: a\n#\n# unrelated\n";
+        assert!(continuation_lines(1, &lines(text), None, None, &regex, true, "").is_empty());
+    }
+
+    #[test]
+    fn test_block_comment() {
+        let text = "/* remind: 2024/01/01\n * first\n * second */\nfn main() {}\n";
+        let syntax = rust_syntax();
+        let ranges = crate::remind::comment::comment_ranges(text, &syntax);
+        let regex = Regex::new(r"remind:\W?").unwrap();
+        let continued = continuation_lines(
+            1,
+            &lines(text),
+            Some(&ranges),
+            Some(&syntax),
+            &regex,
+            true,
+            "",
+        );
+        assert_eq!(continued, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn test_continuation_marker() {
+        let text = "// remind: 2024/01/01 drop this\n// > once the API is stable\n// unrelated\n";
+        let regex = Regex::new(r"remind:\W?").unwrap();
+        let continued = continuation_lines(1, &lines(text), None, None, &regex, false, ">");
+        assert_eq!(continued, vec!["once the API is stable"]);
+    }
+}
@@ -0,0 +1,810 @@
+use std::fmt::{self, Display};
+use std::ops::{Range, RangeInclusive};
+use std::sync::LazyLock;
+
+use anyhow::{anyhow, bail, Error};
+use chrono::format::{parse, Parsed, StrftimeItems};
+use chrono::{DateTime, Datelike, NaiveDateTime, NaiveTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::timezone::Timezone;
+
+const SHORT_MONTH: &str = "(?i:jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)";
+const MONTH: &str = "(?i:jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sep(?:tember)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?)";
+const SHORT_WEEKDAY: &str = "(?i:mon|tue|wed|thu|fri|sat|sun)";
+const WEEKDAY: &str = "(?i:mon(?:day)?|tue(?:sday)?|wed(?:nesday)?|thu(?:rsday)?|fri(?:day)?|sat(?:urday)?|sun(?:day)?)";
+const OFFSET: &str = r"[+\-−]\d{2}:?\d{2}";
+const RFC3339: &str =
+    r"\d{4}-\d{2}-\d{2}[Tt ]\d{2}:\d{2}:\d{2}(?:\.\d{1,9})?(?:[Zz]|[+\-−]\d{2}:?\d{2})";
+
+/// A zone written right after a date, e.g. `2024/06/27 JST`, `2024/06/27 GMT+9` or
+/// `2024/06/27 10:00 +09:00`. A bare offset needs two digits for the hours, so that `-1` is not
+/// read as one.
+static ZONE_SUFFIX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*((?:UTC|GMT)[+\-−]\d{1,2}(?::?\d{2})?\b|[+\-−]\d{2}(?::?\d{2})?\b|[A-Z]{1,5}\b|[A-Za-z_]+(?:/[A-Za-z0-9_+\-]+)+)")
+        .unwrap()
+});
+
+/// An uppercase word after a date that looks like a zone abbreviation, e.g. `CST` or `BRT`.
+static ZONE_LIKE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Z]{2,4}T$").unwrap());
+
+/// Years a deadline is expected in. A date outside them is almost surely a typo, such as
+/// `2204/01/01`.
+const YEARS: RangeInclusive<i32> = 1970..=2199;
+
+/// The `..` between the dates of a range, e.g. `2025/01/01..2025/03/01`.
+static RANGE_SEPARATOR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*\.\.\s*").unwrap());
+
+/// A `datetime_format` together with the pattern that finds its dates in a line.
+pub(crate) struct DatetimeMatcher {
+    format: String,
+    regex: Regex,
+    /// The pattern with zero-padded fields made optional, to tell `2024/6/5` apart from a line
+    /// without a date. `None` when the format has no such fields.
+    unpadded: Option<Regex>,
+}
+
+impl DatetimeMatcher {
+    pub(crate) fn new(format: &str) -> Result<Self, Error> {
+        let regex = try_datetime_format_to_regex(format)?;
+        let unpadded = regex
+            .replace(r"\d{2}", r"\d{1,2}")
+            .replace(r"\d{3}", r"\d{1,3}");
+        let unpadded = match unpadded != regex {
+            true => Some(Regex::new(&digit_bounded(&unpadded))?),
+            false => None,
+        };
+
+        Ok(Self {
+            format: format.to_string(),
+            regex: Regex::new(&digit_bounded(&regex))?,
+            unpadded,
+        })
+    }
+
+    /// The spans of the dates in `line` from `at` on.
+    pub(crate) fn find_iter<'l>(
+        &'l self,
+        line: &'l str,
+        at: usize,
+    ) -> impl Iterator<Item = Range<usize>> + 'l {
+        dates(&self.regex, line, at)
+    }
+
+    /// The span of the first date in `line`.
+    pub(crate) fn find(&self, line: &str) -> Option<Range<usize>> {
+        self.find_iter(line, 0).next()
+    }
+
+    /// The span of a date that starts right at `at`.
+    fn find_at(&self, line: &str, at: usize) -> Option<Range<usize>> {
+        // The guard before the date is the character before `at`.
+        let before = line[..at]
+            .chars()
+            .next_back()
+            .map_or(0, |c| at - c.len_utf8());
+        self.find_iter(line, before)
+            .next()
+            .filter(|span| span.start == at)
+    }
+}
+
+/// Wraps `regex` so that it does not match inside a longer number, e.g. `0240/06/05` in
+/// `20240/06/05`. The regex crate has no lookaround, so the guards consume the characters
+/// around the date, which is captured as `date`.
+fn digit_bounded(regex: &str) -> String {
+    format!(r"(?:^|\D)(?P<date>{})(?:\D|$)", regex)
+}
+
+/// The spans of the `date` group of a `digit_bounded` regex in `line` from `at` on. Each search
+/// resumes right after the previous date, so a single character between two dates guards both.
+fn dates<'l>(
+    regex: &'l Regex,
+    line: &'l str,
+    at: usize,
+) -> impl Iterator<Item = Range<usize>> + 'l {
+    let mut at = Some(at);
+    std::iter::from_fn(move || {
+        let date = regex.captures_at(line, at?)?.name("date")?.range();
+        // An empty date would be found again at the same place.
+        at = (!date.is_empty() && date.end < line.len()).then_some(date.end);
+        Some(date)
+    })
+}
+
+/// Why a date written in a comment cannot be used as a deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidDatetimeReason {
+    /// The date does not exist, e.g. `2024/02/30` or `2024/13/01`.
+    ImpossibleDate,
+    /// A field misses its zero padding, e.g. `2024/6/5` for `%Y/%m/%d`.
+    UnpaddedFields,
+    /// The year is far from any plausible deadline, e.g. `2204/01/01`.
+    YearOutOfRange,
+    /// A range ends before it starts, e.g. `2025/03/01..2025/01/01`.
+    ReversedRange,
+}
+
+impl Display for InvalidDatetimeReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            InvalidDatetimeReason::ImpossibleDate => "impossible date",
+            InvalidDatetimeReason::UnpaddedFields => "unpadded fields",
+            InvalidDatetimeReason::YearOutOfRange => "year out of range",
+            InvalidDatetimeReason::ReversedRange => "reversed range",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// A date in a comment that looks like one of `datetime_format` but cannot be read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvalidDatetime {
+    pub reason: InvalidDatetimeReason,
+    /// The date as written.
+    pub text: String,
+    /// The entry of `datetime_format` the date was read with.
+    pub format: String,
+}
+
+impl Display for InvalidDatetime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} `{}`", self.reason, self.text)?;
+        match self.reason {
+            InvalidDatetimeReason::UnpaddedFields => write!(f, ", expected `{}`", self.format),
+            InvalidDatetimeReason::YearOutOfRange => {
+                write!(f, ", expected {} to {}", YEARS.start(), YEARS.end())
+            }
+            InvalidDatetimeReason::ImpossibleDate | InvalidDatetimeReason::ReversedRange => Ok(()),
+        }
+    }
+}
+
+/// The dates found in a line.
+#[derive(Debug, PartialEq)]
+pub(crate) struct LineDatetime<'m> {
+    /// The deadline, which is the end of a range.
+    pub(crate) datetime: i64,
+    /// The format that parsed the deadline.
+    pub(crate) format: &'m str,
+    /// The start of a range such as `2025/01/01..2025/03/01`.
+    pub(crate) start: Option<i64>,
+    /// Every other date in the line, as written.
+    pub(crate) others: Vec<String>,
+    /// A zone abbreviation written after the date that was not recognised, so the date was
+    /// read in the configured timezone instead.
+    pub(crate) unknown_zone: Option<String>,
+}
+
+/// A date found in a line, with where it was written.
+struct Found<'m> {
+    datetime: i64,
+    format: &'m str,
+    span: Range<usize>,
+    unknown_zone: Option<String>,
+}
+
+/// Parses the first date in `line`, trying each format in order, or the range it starts.
+///
+/// A date without an offset is read in the zone written right after it, or else in `timezone`.
+/// Returns `None` when no format finds a date. A date that is found but rejected by every
+/// format, or found only without its padding, is invalid, and so is a range that ends before
+/// it starts.
+pub(crate) fn parse_line_datetime<'m>(
+    line: &str,
+    matchers: &'m [DatetimeMatcher],
+    timezone: Timezone,
+) -> Result<Option<LineDatetime<'m>>, InvalidDatetime> {
+    let Some(first) = find_datetime(line, None, matchers, timezone)? else {
+        // Only a line without any padded date is looked at again, so that
+        // `2024/06/05 or 2024/6/5` keeps the first date.
+        let unpadded = matchers.iter().find_map(|matcher| {
+            let found = dates(matcher.unpadded.as_ref()?, line, 0).next()?;
+            Some(InvalidDatetime {
+                reason: InvalidDatetimeReason::UnpaddedFields,
+                text: line[found].to_string(),
+                format: matcher.format.clone(),
+            })
+        });
+        return unpadded.map_or(Ok(None), Err);
+    };
+
+    let mut datetime = LineDatetime {
+        datetime: first.datetime,
+        format: first.format,
+        start: None,
+        others: vec![],
+        unknown_zone: first.unknown_zone,
+    };
+    let mut span = first.span.clone();
+    if let Some(separator) = RANGE_SEPARATOR.find(&line[first.span.end..]) {
+        let at = first.span.end + separator.end();
+        if let Some(end) = find_datetime(line, Some(at), matchers, timezone)? {
+            span.end = end.span.end;
+            if end.datetime < first.datetime {
+                return Err(InvalidDatetime {
+                    reason: InvalidDatetimeReason::ReversedRange,
+                    text: line[span].to_string(),
+                    format: first.format.to_string(),
+                });
+            }
+            datetime.start = Some(first.datetime);
+            datetime.datetime = end.datetime;
+            datetime.unknown_zone = datetime.unknown_zone.or(end.unknown_zone);
+        }
+    }
+
+    datetime.others = other_dates(line, matchers, span);
+    Ok(Some(datetime))
+}
+
+/// Finds a date with the first format that has one in `line`, or only a date right at `at`.
+fn find_datetime<'m>(
+    line: &str,
+    at: Option<usize>,
+    matchers: &'m [DatetimeMatcher],
+    timezone: Timezone,
+) -> Result<Option<Found<'m>>, InvalidDatetime> {
+    let mut invalid = None;
+    for matcher in matchers {
+        let found = match at {
+            Some(at) => matcher.find_at(line, at),
+            None => matcher.find(line),
+        };
+        let Some(found) = found else {
+            continue;
+        };
+        let (zone, unknown_zone) = match zone_suffix(&line[found.end..]) {
+            Ok(zone) => (zone.unwrap_or(timezone), None),
+            Err(suffix) => (timezone, Some(suffix)),
+        };
+        let text = &line[found.clone()];
+        let reason = match parse_datetime(text, &matcher.format).and_then(|p| p.timestamp(zone)) {
+            Ok(datetime) if in_years(datetime) => {
+                return Ok(Some(Found {
+                    datetime,
+                    format: &matcher.format,
+                    span: found,
+                    unknown_zone,
+                }))
+            }
+            Ok(_) => InvalidDatetimeReason::YearOutOfRange,
+            Err(_) => InvalidDatetimeReason::ImpossibleDate,
+        };
+        invalid.get_or_insert_with(|| InvalidDatetime {
+            reason,
+            text: text.to_string(),
+            format: matcher.format.clone(),
+        });
+    }
+
+    invalid.map_or(Ok(None), Err)
+}
+
+/// Every date in `line` outside `skip`, in the order they are written.
+fn other_dates(line: &str, matchers: &[DatetimeMatcher], skip: Range<usize>) -> Vec<String> {
+    let mut spans = vec![skip];
+    let mut others = vec![];
+    for matcher in matchers {
+        for found in matcher.find_iter(line, 0) {
+            if spans
+                .iter()
+                .any(|span| found.start < span.end && span.start < found.end)
+            {
+                continue;
+            }
+            others.push((found.start, line[found.clone()].to_string()));
+            spans.push(found);
+        }
+    }
+    others.sort();
+    others.into_iter().map(|(_, text)| text).collect()
+}
+
+fn in_years(timestamp: i64) -> bool {
+    DateTime::from_timestamp(timestamp, 0).is_some_and(|datetime| YEARS.contains(&datetime.year()))
+}
+
+/// The zone written right after a date. A suffix that looks like a zone abbreviation but is
+/// not an accepted one, such as the ambiguous `CST`, or an impossible offset such as `GMT+25`,
+/// is returned as the error.
+fn zone_suffix(rest: &str) -> Result<Option<Timezone>, String> {
+    let Some(suffix) = ZONE_SUFFIX.captures(rest).and_then(|c| c.get(1)) else {
+        return Ok(None);
+    };
+    match Timezone::from_suffix(suffix.as_str()) {
+        Some(zone) => Ok(Some(zone)),
+        None if ZONE_LIKE.is_match(suffix.as_str()) || is_prefixed_offset(suffix.as_str()) => {
+            Err(suffix.as_str().to_string())
+        }
+        None => Ok(None),
+    }
+}
+
+fn is_prefixed_offset(suffix: &str) -> bool {
+    suffix
+        .strip_prefix("UTC")
+        .or_else(|| suffix.strip_prefix("GMT"))
+        .is_some_and(|offset| offset.starts_with(['+', '-', '−']))
+}
+
+/// A parsed date, either pinned to an instant or a wall-clock time still to be placed in a zone.
+pub(crate) enum ParsedDatetime {
+    Instant(i64),
+    Local(NaiveDateTime),
+}
+
+impl ParsedDatetime {
+    pub(crate) fn timestamp(self, timezone: Timezone) -> Result<i64, Error> {
+        match self {
+            ParsedDatetime::Instant(timestamp) => Ok(timestamp),
+            ParsedDatetime::Local(datetime) => timezone.timestamp(datetime),
+        }
+    }
+}
+
+/// Parses `v` with `format`. Dates without a time are read as midnight.
+pub(crate) fn parse_datetime(v: &str, format: &str) -> Result<ParsedDatetime, Error> {
+    if v.is_empty() {
+        return Ok(ParsedDatetime::Instant(0));
+    }
+
+    let mut parsed = Parsed::new();
+    parse(&mut parsed, v, StrftimeItems::new(format))?;
+
+    if parsed.offset().is_some() {
+        return Ok(ParsedDatetime::Instant(parsed.to_datetime()?.timestamp()));
+    }
+    if parsed.timestamp().is_some() {
+        let datetime = parsed.to_datetime_with_timezone(&Utc)?;
+        return Ok(ParsedDatetime::Instant(datetime.timestamp()));
+    }
+
+    let date = parsed.to_naive_date()?;
+    let time = match parsed.hour_div_12() {
+        Some(_) => parsed.to_naive_time()?,
+        None => NaiveTime::default(),
+    };
+    Ok(ParsedDatetime::Local(date.and_time(time)))
+}
+
+/// Parses a date written in the config, such as a milestone, with any of `formats` or as
+/// `YYYY-MM-DD`.
+pub(crate) fn parse_config_datetime(
+    v: &str,
+    formats: &[String],
+    timezone: Timezone,
+) -> Result<i64, Error> {
+    formats
+        .iter()
+        .map(String::as_str)
+        .chain(["%Y-%m-%d"])
+        .find_map(|format| {
+            parse_datetime(v, format)
+                .and_then(|p| p.timestamp(timezone))
+                .ok()
+        })
+        .ok_or_else(|| anyhow!("`{}` does not match `datetime_format`", v))
+}
+
+/// Translates a strftime format into a regex that finds the dates it parses.
+///
+/// Only `%Y`, `%m`, `%d`, `%H`, `%M` and `%S` were translated before; a format this cannot
+/// translate is now matched literally.
+#[deprecated(note = "use `try_datetime_format_to_regex`, which reports unsupported specifiers")]
+pub fn datetime_format_to_regex(format: &str) -> String {
+    try_datetime_format_to_regex(format).unwrap_or_else(|_| regex::escape(format))
+}
+
+/// Translates a strftime format into a regex that finds the dates it parses.
+///
+/// Every specifier of chrono is supported, and the text around them is matched literally.
+/// The regex has no digit boundaries of its own, so that it can be embedded in another one.
+pub fn try_datetime_format_to_regex(format: &str) -> Result<String, Error> {
+    translate(format, format, &escape_literal)
+}
+
+/// Like `try_datetime_format_to_regex`, but the text around the specifiers is a regex itself,
+/// as in the `format` of `validates`.
+pub fn validate_format_to_regex(format: &str) -> Result<String, Error> {
+    translate(format, format, &str::to_string)
+}
+
+fn translate(
+    format: &str,
+    origin: &str,
+    literal: &dyn Fn(&str) -> String,
+) -> Result<String, Error> {
+    let mut regex = String::new();
+    let mut text = String::new();
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            text.push(c);
+            continue;
+        }
+
+        let mut spec = String::new();
+        for c in chars.by_ref() {
+            spec.push(c);
+            if c.is_ascii_alphabetic() || c == '%' || c == '+' {
+                break;
+            }
+        }
+
+        if spec == "%" {
+            text.push('%');
+            continue;
+        }
+        regex.push_str(&literal(&text));
+        text.clear();
+        regex.push_str(&specifier_to_regex(&spec, origin)?);
+    }
+    regex.push_str(&literal(&text));
+
+    Ok(regex)
+}
+
+#[derive(Clone, Copy)]
+enum Pad {
+    Zero,
+    Space,
+    None,
+}
+
+fn specifier_to_regex(spec: &str, origin: &str) -> Result<String, Error> {
+    let (pad, name) = match spec.chars().next() {
+        Some('-') if spec.len() > 1 => (Some(Pad::None), &spec[1..]),
+        Some('_') if spec.len() > 1 => (Some(Pad::Space), &spec[1..]),
+        Some('0') if spec.len() > 1 => (Some(Pad::Zero), &spec[1..]),
+        _ => (None, spec),
+    };
+    let number = |width: usize, default: Pad| digits(width, pad.unwrap_or(default));
+    let expand = |format: &str| translate(format, origin, &escape_literal);
+
+    let regex = match name {
+        "Y" | "G" => number(4, Pad::Zero),
+        "C" | "y" | "g" | "m" | "d" | "U" | "W" | "V" | "H" | "I" | "M" | "S" => {
+            number(2, Pad::Zero)
+        }
+        "e" | "k" | "l" => number(2, Pad::Space),
+        "q" | "u" | "w" => number(1, Pad::Zero),
+        "j" => number(3, Pad::Zero),
+        "f" => number(9, Pad::Zero),
+        "3f" => number(3, Pad::Zero),
+        "6f" => number(6, Pad::Zero),
+        "9f" => number(9, Pad::Zero),
+        ".f" | ".3f" | ".6f" | ".9f" => r"(?:\.\d{1,9})?".to_string(),
+        "s" => r"\d+".to_string(),
+        "b" | "h" => SHORT_MONTH.to_string(),
+        "B" => MONTH.to_string(),
+        "a" => SHORT_WEEKDAY.to_string(),
+        "A" => WEEKDAY.to_string(),
+        "p" | "P" => "(?i:[ap]m)".to_string(),
+        // chrono reads only the hours and minutes of an offset, whatever its notation.
+        "z" | ":z" | "::z" | ":::z" => OFFSET.to_string(),
+        "#z" => r"(?:[Zz]|[+\-−]\d{2}(?::?\d{2})?)".to_string(),
+        "Z" => r"\S+".to_string(),
+        "n" | "t" => r"\s+".to_string(),
+        "+" => RFC3339.to_string(),
+        "F" => expand("%Y-%m-%d")?,
+        "D" | "x" => expand("%m/%d/%y")?,
+        "v" => expand("%e-%b-%Y")?,
+        "R" => expand("%H:%M")?,
+        "T" | "X" => expand("%H:%M:%S")?,
+        "r" => expand("%I:%M:%S %p")?,
+        "c" => expand("%a %b %e %H:%M:%S %Y")?,
+        _ => bail!(
+            "unsupported specifier `%{}` in datetime format `{}`",
+            spec,
+            origin
+        ),
+    };
+
+    Ok(regex)
+}
+
+fn digits(width: usize, pad: Pad) -> String {
+    match (pad, width) {
+        (Pad::Zero, _) => format!(r"\d{{{}}}", width),
+        (_, 1) => r"\d".to_string(),
+        (Pad::Space, _) => format!(r" ?\d{{1,{}}}", width),
+        (Pad::None, _) => format!(r"\d{{1,{}}}", width),
+    }
+}
+
+/// Escapes literal text, letting any run of whitespace match any whitespace as chrono does.
+fn escape_literal(text: &str) -> String {
+    let mut regex = String::new();
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                regex.push_str(r"\s+");
+            }
+            in_space = true;
+            continue;
+        }
+        in_space = false;
+        regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4])));
+    }
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, FixedOffset};
+    use pretty_assertions::assert_eq;
+
+    fn deadline<'m>(
+        line: &str,
+        matchers: &'m [DatetimeMatcher],
+        timezone: Timezone,
+    ) -> Option<(i64, &'m str)> {
+        parse_line_datetime(line, matchers, timezone)
+            .unwrap()
+            .map(|parsed| (parsed.datetime, parsed.format))
+    }
+
+    fn matchers(formats: &[&str]) -> Vec<DatetimeMatcher> {
+        formats
+            .iter()
+            .map(|format| DatetimeMatcher::new(format).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_line_datetime_formats() {
+        let matchers = matchers(&["%Y/%m/%d", "%Y-%m-%dT%H:%M", "%Y-%m-%d"]);
+
+        let utc = Timezone::default();
+        let parsed = deadline("// remind: 2024/06/27", &matchers, utc);
+        assert_eq!(parsed, Some((1719446400, "%Y/%m/%d")));
+
+        let parsed = deadline("// remind: 2024-06-27T10:00", &matchers, utc);
+        assert_eq!(parsed, Some((1719482400, "%Y-%m-%dT%H:%M")));
+
+        let parsed = deadline("// remind: 2024-06-27", &matchers, utc);
+        assert_eq!(parsed, Some((1719446400, "%Y-%m-%d")));
+
+        let parsed = deadline("// remind: someday", &matchers, utc);
+        assert_eq!(parsed, None);
+
+        assert!(parse_line_datetime("// remind: 2024-02-30", &matchers, utc).is_err());
+    }
+
+    #[test]
+    fn test_parse_line_datetime_invalid() {
+        let matchers = matchers(&["%Y/%m/%d"]);
+        let utc = Timezone::default();
+        let reason = |line| {
+            parse_line_datetime(line, &matchers, utc)
+                .unwrap_err()
+                .reason
+        };
+
+        assert_eq!(
+            reason("// remind: 2024/02/30 remove"),
+            InvalidDatetimeReason::ImpossibleDate
+        );
+        assert_eq!(
+            reason("// remind: 2024/13/01 remove"),
+            InvalidDatetimeReason::ImpossibleDate
+        );
+        assert_eq!(
+            reason("// remind: 2024/6/5 remove"),
+            InvalidDatetimeReason::UnpaddedFields
+        );
+        assert_eq!(
+            reason("// remind: 2024/06/5 remove"),
+            InvalidDatetimeReason::UnpaddedFields
+        );
+        assert_eq!(
+            reason("// remind: 2204/06/05 remove"),
+            InvalidDatetimeReason::YearOutOfRange
+        );
+        // A date inside a longer number is not a date at all.
+        assert_eq!(
+            parse_line_datetime("// remind: 20240/06/05 remove", &matchers, utc),
+            Ok(None)
+        );
+        assert_eq!(
+            parse_line_datetime("// remind: 2024/06/055 remove", &matchers, utc),
+            Ok(None)
+        );
+
+        let invalid = parse_line_datetime("// remind: 2024/6/5", &matchers, utc).unwrap_err();
+        assert_eq!(
+            invalid.to_string(),
+            "unpadded fields `2024/6/5`, expected `%Y/%m/%d`"
+        );
+        assert_eq!(deadline("// remind: see #12/3", &matchers, utc), None);
+    }
+
+    #[test]
+    fn test_parse_line_datetime_ranges() {
+        let matchers = matchers(&["%Y/%m/%d", "%Y-%m-%d"]);
+        let utc = Timezone::default();
+        let parse = |line| parse_line_datetime(line, &matchers, utc);
+
+        let range = parse("// remind: 2025/01/01..2025/03/01 migrate")
+            .unwrap()
+            .unwrap();
+        assert_eq!(range.start, Some(1735689600));
+        assert_eq!(range.datetime, 1740787200);
+        assert!(range.others.is_empty());
+
+        let range = parse("// remind: 2025/01/01 .. 2025-03-01")
+            .unwrap()
+            .unwrap();
+        assert_eq!(range.start, Some(1735689600));
+        assert_eq!(range.datetime, 1740787200);
+
+        let reversed = parse("// remind: 2025/03/01..2025/01/01").unwrap_err();
+        assert_eq!(reversed.reason, InvalidDatetimeReason::ReversedRange);
+        assert_eq!(reversed.text, "2025/03/01..2025/01/01");
+        assert_eq!(
+            parse("// remind: 2025/01/01..2025/02/30")
+                .unwrap_err()
+                .reason,
+            InvalidDatetimeReason::ImpossibleDate
+        );
+
+        let several = parse("// remind: see 2024-06-01, due 2025/01/01 or 2025/02/01")
+            .unwrap()
+            .unwrap();
+        assert_eq!(several.start, None);
+        assert_eq!(several.datetime, 1735689600);
+        assert_eq!(several.others, vec!["2024-06-01", "2025/02/01"]);
+    }
+
+    #[test]
+    fn test_formats_round_trip_through_chrono() {
+        let datetime =
+            DateTime::<FixedOffset>::parse_from_rfc3339("2024-06-05T07:08:09+09:00").unwrap();
+        let date = 1717545600;
+        let local_time = 1717571289;
+        let instant = 1717538889;
+        let cases = [
+            ("%Y/%m/%d", date),
+            ("%Y.%m.%d", date),
+            ("%Y年%m月%d日", date),
+            ("%b %d, %Y", date),
+            ("%B %e %Y", date),
+            ("%A, %-d %B %Y", date),
+            ("%y-%m-%d", date),
+            ("%j/%Y", date),
+            ("%F", date),
+            ("%D", date),
+            ("%v", date),
+            ("%F %T", local_time),
+            ("%Y/%m/%d %I:%M:%S %p", local_time),
+            ("%c", local_time),
+            ("%F %T%.3f", local_time),
+            ("%FT%T%:z", instant),
+            ("%Y-%m-%d %H:%M:%S %z", instant),
+            ("%+", instant),
+            ("%s", instant),
+        ];
+
+        for (format, expected) in cases {
+            let formatted = datetime.format(format).to_string();
+            let line = format!("// remind: {} remove this", formatted);
+            let matcher = DatetimeMatcher::new(format).unwrap();
+
+            let found = matcher.find(&line).map(|span| &line[span]);
+            assert_eq!(found, Some(formatted.as_str()), "format `{}`", format);
+            let parsed = parse_datetime(&formatted, format)
+                .and_then(|p| p.timestamp(Timezone::default()))
+                .unwrap();
+            assert_eq!(parsed, expected, "format `{}`", format);
+        }
+    }
+
+    #[test]
+    fn test_parse_line_datetime_timezones() {
+        let matchers = matchers(&["%Y/%m/%d %H:%M", "%Y/%m/%d"]);
+        let tokyo = "Asia/Tokyo".parse::<Timezone>().unwrap();
+        let cases = [
+            (
+                "// remind: 2024/06/27 remove this",
+                Timezone::default(),
+                1719446400,
+            ),
+            ("// remind: 2024/06/27 remove this", tokyo, 1719414000),
+            (
+                "// remind: 2024/06/27 JST remove this",
+                Timezone::default(),
+                1719414000,
+            ),
+            (
+                "// remind: 2024/06/27 Asia/Tokyo",
+                Timezone::default(),
+                1719414000,
+            ),
+            (
+                "// remind: 2024/06/27 09:00 +09:00",
+                Timezone::default(),
+                1719446400,
+            ),
+            ("// remind: 2024/06/27 UTC remove this", tokyo, 1719446400),
+            ("// remind: 2024/06/27 TODO remove this", tokyo, 1719414000),
+            ("// remind: 2024/06/27 EST", Timezone::default(), 1719464400),
+            ("// remind: 2024/06/27 EDT", Timezone::default(), 1719460800),
+            ("// remind: 2024/06/27 MST", Timezone::default(), 1719471600),
+            ("// remind: 2024/06/27 HST", Timezone::default(), 1719482400),
+            ("// remind: 2024/06/27 CET", Timezone::default(), 1719442800),
+            (
+                "// remind: 2024/06/27 GMT+9",
+                Timezone::default(),
+                1719414000,
+            ),
+            (
+                "// remind: 2024/06/27 UTC-5 call",
+                Timezone::default(),
+                1719464400,
+            ),
+            (
+                "// remind: 2024/06/27 GMT+5:30",
+                Timezone::default(),
+                1719426600,
+            ),
+            ("// remind: 2024/06/27 GMT remove", tokyo, 1719446400),
+            ("// remind: 2024/06/27 -1 day", tokyo, 1719414000),
+        ];
+
+        for (line, timezone, expected) in cases {
+            let parsed = deadline(line, &matchers, timezone);
+            assert_eq!(
+                parsed.map(|(datetime, _)| datetime),
+                Some(expected),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_datetime_format_to_regex_escapes_literals() {
+        let matcher = DatetimeMatcher::new("%Y.%m.%d").unwrap();
+        assert!(matcher.regex.is_match("2024.06.05"));
+        assert!(!matcher.regex.is_match("2024x06x05"));
+
+        let matcher = DatetimeMatcher::new("(%Y)").unwrap();
+        assert!(matcher.regex.is_match("(2024)"));
+        assert!(!matcher.regex.is_match("2024"));
+
+        assert!(try_datetime_format_to_regex("%Y/%Q").is_err());
+        assert!(try_datetime_format_to_regex("%Y/%").is_err());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_datetime_format_to_regex() {
+        assert_eq!(datetime_format_to_regex("%Y/%m/%d"), r"\d{4}/\d{2}/\d{2}");
+        assert_eq!(datetime_format_to_regex("%Y/%Q"), r"%Y/%Q");
+    }
+
+    #[test]
+    fn test_matcher_digit_boundaries() {
+        let matcher = DatetimeMatcher::new("%Y/%m/%d").unwrap();
+        let line = "12024/06/05 2024/06/06 2024/06/07x 2024/06/080";
+        let dates = matcher
+            .find_iter(line, 0)
+            .map(|span| &line[span])
+            .collect::<Vec<_>>();
+        assert_eq!(dates, vec!["2024/06/06", "2024/06/07"]);
+        assert_eq!(matcher.find_at(line, 12), Some(12..22));
+        assert_eq!(matcher.find_at(line, 1), None);
+    }
+
+    #[test]
+    fn test_validate_format_to_regex() {
+        let regex = validate_format_to_regex("@(alice|bob) %Y/%m/%d").unwrap();
+        assert_eq!(regex, r"@(alice|bob) \d{4}/\d{2}/\d{2}");
+    }
+}
@@ -0,0 +1,90 @@
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+
+/// A problem found while scanning that did not stop the scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// A directory entry could not be read while walking the search directories.
+    WalkError,
+    /// A file could not be read or searched, e.g. because it is not valid UTF-8.
+    SearchError,
+    /// A file was skipped because it looks binary.
+    BinaryFile,
+    /// A milestone or period was written but could not be resolved to a date.
+    InvalidDatetime,
+    /// A typed meta placeholder captured a value it cannot read, e.g. an impossible date for
+    /// `${due:date}`.
+    InvalidMeta,
+    /// A date is followed by what looks like a zone abbreviation but is not a known, unambiguous
+    /// one, so the date was read in the configured timezone.
+    UnknownTimezone,
+    /// A line has several dates that are not a range, so only the first one is the deadline.
+    MultipleDates,
+    /// A trigger could not be decided, e.g. because the project version could not be read.
+    TriggerError,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(
+        kind: DiagnosticKind,
+        path: Option<String>,
+        line: Option<u64>,
+        message: impl Display,
+    ) -> Self {
+        Self {
+            kind,
+            path,
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl DiagnosticKind {
+    /// Whether the problem kept a whole file from being searched.
+    pub fn skips_file(self) -> bool {
+        matches!(
+            self,
+            DiagnosticKind::WalkError | DiagnosticKind::SearchError | DiagnosticKind::BinaryFile
+        )
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.path, self.line) {
+            (Some(path), Some(line)) => write!(f, "{}:{} ", path, line)?,
+            (Some(path), None) => write!(f, "{} ", path)?,
+            _ => {}
+        }
+        write!(f, "{}: {}", self.kind, self.message)
+    }
+}
+
+impl Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DiagnosticKind::WalkError => "walk error",
+            DiagnosticKind::SearchError => "search error",
+            DiagnosticKind::BinaryFile => "binary file",
+            DiagnosticKind::InvalidDatetime => "invalid datetime",
+            DiagnosticKind::InvalidMeta => "invalid meta",
+            DiagnosticKind::UnknownTimezone => "unknown timezone",
+            DiagnosticKind::MultipleDates => "multiple dates",
+            DiagnosticKind::TriggerError => "trigger error",
+        };
+        f.write_str(name)
+    }
+}
@@ -0,0 +1,19 @@
+/// FNV-1a, as fingerprints are committed, and the scan cache is kept between runs, so neither
+/// may change between Rust versions like `DefaultHasher` may.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_fnv1a_is_stable() {
+        // Committed fingerprints and cache files written by earlier builds must keep matching.
+        assert_eq!(fnv1a(b"// remind: 2024/06/27\n"), 0x4bae0c5b276060bd);
+    }
+}
@@ -1,41 +1,327 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
 
+use anyhow::{anyhow, bail, Error};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
-fn convert_pattern_to_regex(pattern: &str) -> String {
-    let placeholder_re = Regex::new(r"\$\{(\w+)\}").unwrap();
-    placeholder_re
-        .replace_all(pattern, "(?P<$1>.*)")
-        .to_string()
+use super::datetime::{parse_config_datetime, try_datetime_format_to_regex};
+use crate::config::timezone::Timezone;
+
+/// What `${name:user}` matches: a handle such as `alice` or `bob.smith`, without the
+/// punctuation that may follow it in a sentence.
+const USER: &str = r"\w(?:[\w.\-]*\w)?";
+
+/// The value of a meta placeholder. Typed placeholders keep what their value was read as.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MetaValue {
+    Text(String),
+    /// A value of `${name:enum(...)}` with its position in the list, so that `P0` comes before
+    /// `P1`.
+    Enum {
+        value: String,
+        index: usize,
+    },
+    /// A value of `${name:date}` as written, with its Unix timestamp.
+    Date {
+        value: String,
+        timestamp: i64,
+    },
+}
+
+impl MetaValue {
+    /// The value as written in the comment.
+    pub fn as_str(&self) -> &str {
+        match self {
+            MetaValue::Text(value)
+            | MetaValue::Enum { value, .. }
+            | MetaValue::Date { value, .. } => value,
+        }
+    }
+}
+
+impl Display for MetaValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
-pub(crate) fn extract_placeholders(pattern: &str, text: &str) -> Option<HashMap<String, String>> {
-    let re_pattern = convert_pattern_to_regex(pattern);
-    let re = Regex::new(&re_pattern).unwrap();
+/// What a placeholder matches: `${name}` anything, `${name:type}` a value of that type.
+#[derive(Debug, Clone, PartialEq)]
+enum PlaceholderKind {
+    Any,
+    User,
+    Regex(String),
+    Enum(Vec<String>),
+    Date,
+}
+
+impl PlaceholderKind {
+    fn parse(kind: &str, argument: Option<&str>) -> Result<Self, Error> {
+        match (kind, argument) {
+            ("user", None) => Ok(PlaceholderKind::User),
+            ("date", None) => Ok(PlaceholderKind::Date),
+            ("regex", Some(regex)) => {
+                Regex::new(regex).map_err(|e| anyhow!("invalid regex `{}`: {}", regex, e))?;
+                Ok(PlaceholderKind::Regex(regex.to_string()))
+            }
+            ("enum", Some(values)) => {
+                let values: Vec<String> = values
+                    .split(',')
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+                    .collect();
+                if values.is_empty() {
+                    bail!("`enum` needs at least one value");
+                }
+                Ok(PlaceholderKind::Enum(values))
+            }
+            ("user" | "date", Some(_)) => bail!("`{}` takes no arguments", kind),
+            ("regex" | "enum", None) => bail!("`{}` needs arguments, e.g. `{}(...)`", kind, kind),
+            _ => bail!(
+                "unknown placeholder type `{}`, expected user, regex, enum or date",
+                kind
+            ),
+        }
+    }
+
+    /// The pattern of the value. `date` is the alternation of the `datetime_format` patterns.
+    fn regex(&self, date: &str) -> String {
+        match self {
+            PlaceholderKind::Any => ".*".to_string(),
+            PlaceholderKind::User => USER.to_string(),
+            PlaceholderKind::Regex(regex) => regex.clone(),
+            PlaceholderKind::Enum(values) => {
+                // The longest value first, so that `P10` is not read as `P1`, and a word
+                // boundary after each, so that `P1` is not read out of `P12`.
+                let mut values = values.clone();
+                values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+                values
+                    .iter()
+                    .map(|value| {
+                        let ends_in_word =
+                            value.ends_with(|c: char| c.is_alphanumeric() || c == '_');
+                        format!(
+                            "{}{}",
+                            regex::escape(value),
+                            if ends_in_word { r"\b" } else { "" }
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("|")
+            }
+            PlaceholderKind::Date => date.to_string(),
+        }
+    }
+}
+
+/// A part of `comment_regex`: regex text as written, or a placeholder.
+enum Segment<'p> {
+    Text(&'p str),
+    Placeholder(String, PlaceholderKind),
+}
+
+/// Splits `pattern` into its text and its `${name}` or `${name:type(arguments)}` placeholders.
+/// `${` that does not start a name is left to the regex.
+fn parse_pattern(pattern: &str) -> Result<Vec<Segment<'_>>, Error> {
+    let mut segments = vec![];
+    let mut text_start = 0;
+    let mut rest = pattern;
+
+    while let Some(at) = rest.find("${") {
+        let offset = pattern.len() - rest.len();
+        let body = &rest[at + 2..];
+        let name_len = body
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(body.len());
+        let name = &body[..name_len];
+        let after_name = &body[name_len..];
+
+        let (kind, consumed) = match after_name.chars().next() {
+            Some('}') if !name.is_empty() => (PlaceholderKind::Any, name_len + 1),
+            Some(':') if !name.is_empty() => {
+                let typed = &after_name[1..];
+                let (kind, argument, len) = typed_placeholder(typed)
+                    .ok_or_else(|| anyhow!("unclosed placeholder `${{{}:{}`", name, typed))?;
+                let kind = PlaceholderKind::parse(kind, argument)
+                    .map_err(|e| anyhow!("invalid placeholder `${{{}:{}}}`: {}", name, kind, e))?;
+                (kind, name_len + 1 + len)
+            }
+            _ => {
+                rest = &rest[at + 2..];
+                continue;
+            }
+        };
+
+        segments.push(Segment::Text(&pattern[text_start..offset + at]));
+        segments.push(Segment::Placeholder(name.to_string(), kind));
+        rest = &body[consumed..];
+        text_start = pattern.len() - rest.len();
+    }
+    segments.push(Segment::Text(&pattern[text_start..]));
+
+    Ok(segments)
+}
+
+/// Reads `type}` or `type(arguments)}`, returning them with the length read. Parentheses in the
+/// arguments nest, and a backslash escapes the character after it.
+fn typed_placeholder(text: &str) -> Option<(&str, Option<&str>, usize)> {
+    let kind_len = text
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(text.len());
+    let kind = &text[..kind_len];
+    let rest = &text[kind_len..];
+
+    if rest.starts_with('}') {
+        return Some((kind, None, kind_len + 1));
+    }
+    if !rest.starts_with('(') {
+        return None;
+    }
 
-    re.captures(text).map(|captures| {
-        let mut map = HashMap::new();
-        for name in re.capture_names().flatten() {
-            if let Some(value) = captures.name(name) {
-                map.insert(name.to_string(), value.as_str().to_string());
+    let mut depth = 0;
+    let mut escaped = false;
+    for (i, c) in rest.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    let argument = &rest[1..i];
+                    return rest[i + 1..].starts_with('}').then_some((
+                        kind,
+                        Some(argument),
+                        kind_len + i + 2,
+                    ));
+                }
             }
+            _ => {}
         }
-        map
+    }
+    None
+}
+
+/// The alternation of the patterns of `formats`, for `${name:date}`.
+fn date_regex(formats: &[String]) -> Result<String, Error> {
+    let patterns = formats
+        .iter()
+        .map(|format| try_datetime_format_to_regex(format).map(|regex| format!("(?:{})", regex)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(patterns.join("|"))
+}
+
+fn convert_pattern(
+    pattern: &str,
+    formats: &[String],
+    group: impl Fn(&str, &str) -> String,
+) -> Result<String, Error> {
+    let date = date_regex(formats)?;
+    let regex = parse_pattern(pattern)?
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.to_string(),
+            Segment::Placeholder(name, kind) => group(&name, &kind.regex(&date)),
+        })
+        .collect();
+    Ok(regex)
+}
+
+fn convert_pattern_to_regex(pattern: &str, formats: &[String]) -> Result<String, Error> {
+    convert_pattern(pattern, formats, |name, regex| {
+        format!("(?P<{}>{})", name, regex)
     })
 }
 
-pub(crate) fn convert_meta_regex(reg_str: &str) -> String {
-    let meta_regex = Regex::new(r"\$\{(\w+)\}").unwrap();
-    let reg = meta_regex.replace_all(reg_str, "(.*)").to_string();
+/// `comment_regex` with its placeholders as unnamed groups, for the line matcher.
+pub(crate) fn convert_meta_regex(pattern: &str, formats: &[String]) -> Result<String, Error> {
+    convert_pattern(pattern, formats, |_, regex| format!("({})", regex))
+}
+
+/// The names of the meta fields `comment_regex` captures, from placeholders or named groups.
+pub(crate) fn meta_names(pattern: &str, formats: &[String]) -> Result<HashSet<String>, Error> {
+    let regex = Regex::new(&convert_pattern_to_regex(pattern, formats)?)?;
+    Ok(regex
+        .capture_names()
+        .flatten()
+        .map(str::to_string)
+        .collect())
+}
 
-    reg
+/// Reads the meta of a reminder with the placeholders of `comment_regex`.
+pub(crate) struct MetaMatcher {
+    regex: Regex,
+    kinds: HashMap<String, PlaceholderKind>,
+    formats: Vec<String>,
+    timezone: Timezone,
 }
 
-pub(crate) fn contains_meta_matcher(pattern: &str) -> bool {
-    let converted_pattern = convert_pattern_to_regex(pattern);
-    Regex::new(r"\(\?P<\w+>.*\)")
-        .unwrap()
-        .is_match(&converted_pattern)
+impl MetaMatcher {
+    pub(crate) fn new(
+        pattern: &str,
+        formats: &[String],
+        timezone: Timezone,
+    ) -> Result<Self, Error> {
+        let kinds = parse_pattern(pattern)?
+            .into_iter()
+            .filter_map(|segment| match segment {
+                Segment::Placeholder(name, kind) => Some((name, kind)),
+                Segment::Text(_) => None,
+            })
+            .collect();
+
+        Ok(Self {
+            regex: Regex::new(&convert_pattern_to_regex(pattern, formats)?)?,
+            kinds,
+            formats: formats.to_vec(),
+            timezone,
+        })
+    }
+
+    /// The meta of `text`, which is empty when the pattern does not match. A `${name:date}`
+    /// value that is not a valid date is kept as text and reported in `problems`.
+    pub(crate) fn extract(
+        &self,
+        text: &str,
+        problems: &mut Vec<String>,
+    ) -> HashMap<String, MetaValue> {
+        let Some(captures) = self.regex.captures(text) else {
+            return HashMap::new();
+        };
+
+        let mut meta = HashMap::new();
+        for name in self.regex.capture_names().flatten() {
+            let Some(value) = captures.name(name) else {
+                continue;
+            };
+            let value = value.as_str().to_string();
+            let value = match self.kinds.get(name) {
+                Some(PlaceholderKind::Enum(values)) => {
+                    match values.iter().position(|v| *v == value) {
+                        Some(index) => MetaValue::Enum { value, index },
+                        None => MetaValue::Text(value),
+                    }
+                }
+                Some(PlaceholderKind::Date) => {
+                    match parse_config_datetime(&value, &self.formats, self.timezone) {
+                        Ok(timestamp) => MetaValue::Date { value, timestamp },
+                        Err(_) => {
+                            problems.push(format!(
+                                "`${{{}:date}}` is not a valid date: `{}`",
+                                name, value
+                            ));
+                            MetaValue::Text(value)
+                        }
+                    }
+                }
+                _ => MetaValue::Text(value),
+            };
+            meta.insert(name.to_string(), value);
+        }
+        meta
+    }
 }
 
 #[cfg(test)]
@@ -43,18 +329,38 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    fn formats() -> Vec<String> {
+        vec!["%Y/%m/%d".to_string()]
+    }
+
+    fn extract_placeholders(pattern: &str, text: &str) -> Option<HashMap<String, String>> {
+        let matcher = MetaMatcher::new(pattern, &formats(), Timezone::default()).unwrap();
+        let meta = matcher.extract(text, &mut vec![]);
+        matcher.regex.is_match(text).then(|| {
+            meta.into_iter()
+                .map(|(name, value)| (name, value.as_str().to_string()))
+                .collect()
+        })
+    }
+
     #[test]
     fn test_convert_pattern_to_regex() {
         let pattern = r"@${assignee} remind:\W?";
         let expected = r"@(?P<assignee>.*) remind:\W?";
-        assert_eq!(convert_pattern_to_regex(pattern), expected);
+        assert_eq!(
+            convert_pattern_to_regex(pattern, &formats()).unwrap(),
+            expected
+        );
     }
 
     #[test]
     fn test_convert_pattern_to_regex_multiple_placeholders() {
         let pattern = r"@${assignee} remind: ${task}\W?";
         let expected = r"@(?P<assignee>.*) remind: (?P<task>.*)\W?";
-        assert_eq!(convert_pattern_to_regex(pattern), expected);
+        assert_eq!(
+            convert_pattern_to_regex(pattern, &formats()).unwrap(),
+            expected
+        );
     }
 
     #[test]
@@ -85,14 +391,14 @@ mod tests {
     fn test_convert_meta_regex() {
         let reg_str = r"@${assignee} remind:\W?";
         let expected = r"@(.*) remind:\W?";
-        assert_eq!(convert_meta_regex(reg_str), expected);
+        assert_eq!(convert_meta_regex(reg_str, &formats()).unwrap(), expected);
     }
 
     #[test]
     fn test_convert_meta_regex_multiple_placeholders() {
         let reg_str = r"@${assignee} remind: ${task}\W?";
         let expected = r"@(.*) remind: (.*)\W?";
-        assert_eq!(convert_meta_regex(reg_str), expected);
+        assert_eq!(convert_meta_regex(reg_str, &formats()).unwrap(), expected);
     }
 
     #[test]
@@ -160,4 +466,88 @@ mod tests {
 
         assert_eq!(extract_placeholders(pattern, text), expected);
     }
+
+    #[test]
+    fn test_typed_placeholders() {
+        let pattern = r"@${assignee:user} ${ticket:regex([A-Z]+-\d+)} ${priority:enum(P1,P10)} remind: ${due:date}";
+        let expected = r"@(?P<assignee>\w(?:[\w.\-]*\w)?) (?P<ticket>[A-Z]+-\d+) (?P<priority>P10\b|P1\b) remind: (?P<due>(?:\d{4}/\d{2}/\d{2}))";
+        assert_eq!(
+            convert_pattern_to_regex(pattern, &formats()).unwrap(),
+            expected
+        );
+
+        let matcher = MetaMatcher::new(pattern, &formats(), Timezone::default()).unwrap();
+        let meta = matcher.extract(
+            "// @alice. OPS-12 P10 remind: 2024/06/27 drop it",
+            &mut vec![],
+        );
+        assert_eq!(meta.len(), 0);
+
+        let mut problems = vec![];
+        let meta = matcher.extract(
+            "// @alice OPS-12 P10 remind: 2024/06/27 drop it",
+            &mut problems,
+        );
+        assert_eq!(meta["assignee"], MetaValue::Text("alice".into()));
+        assert_eq!(meta["ticket"], MetaValue::Text("OPS-12".into()));
+        assert_eq!(
+            meta["priority"],
+            MetaValue::Enum {
+                value: "P10".into(),
+                index: 1
+            }
+        );
+        assert_eq!(
+            meta["due"],
+            MetaValue::Date {
+                value: "2024/06/27".into(),
+                timestamp: 1719446400
+            }
+        );
+        assert!(problems.is_empty());
+
+        let meta = matcher.extract("// @bob OPS-1 P1 remind: 2024/02/30", &mut problems);
+        assert_eq!(meta["due"], MetaValue::Text("2024/02/30".into()));
+        assert_eq!(problems.len(), 1);
+
+        // A listed value must not be read out of a longer word.
+        let matcher = MetaMatcher::new(
+            "${priority:enum(P0,P1,P2)} remind:",
+            &formats(),
+            Timezone::default(),
+        )
+        .unwrap();
+        let meta = matcher.extract("// P12 remind: 2024/06/27", &mut vec![]);
+        assert_eq!(meta.get("priority"), None);
+        let meta = matcher.extract("// P1 remind: 2024/06/27", &mut vec![]);
+        assert_eq!(
+            meta["priority"],
+            MetaValue::Enum {
+                value: "P1".into(),
+                index: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_placeholders() {
+        let formats = formats();
+        let error = |pattern| {
+            convert_pattern_to_regex(pattern, &formats)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert!(error("${assignee:team}").contains("unknown placeholder type `team`"));
+        assert!(error("${ticket:regex([A-Z+)}").contains("invalid regex"));
+        assert!(error("${priority:enum()}").contains("at least one value"));
+        assert!(error("${priority:enum}").contains("needs arguments"));
+        assert!(error("${ticket:regex((a|b)").contains("unclosed placeholder"));
+
+        // `${` without a name is left to the regex, as before typed placeholders.
+        assert_eq!(
+            convert_pattern_to_regex(r"(${ |x) remind:", &formats).unwrap(),
+            r"(${ |x) remind:"
+        );
+    }
 }
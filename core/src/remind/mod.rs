@@ -1,148 +1,408 @@
-use ::regex::{Regex, RegexBuilder};
 use anyhow::Error;
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
-use grep_regex::RegexMatcherBuilder;
-use grep_searcher::sinks::UTF8;
-use grep_searcher::SearcherBuilder;
-use ignore::WalkBuilder;
-use meta::{convert_meta_regex, extract_placeholders};
-use serde::Serialize;
+use chrono::Utc;
+use scanner::Scanner;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::io;
+use std::path::Path;
 
 use crate::config::Config;
+use crate::git::Repo;
+use crate::Reminders;
+use recurrence::resolve_recurrences;
+use relative::resolve_relative_deadlines;
+use trigger::TriggerContext;
 
+mod cache;
+mod comment;
+mod continuation;
+mod datetime;
+mod diagnostic;
+mod hash;
 pub(crate) mod meta;
+mod period;
+mod recurrence;
+pub(crate) mod relative;
+mod scanner;
+mod severity;
+mod snooze;
+mod stream;
+pub(crate) mod trigger;
+mod validate;
 
-#[derive(Debug, Serialize)]
+#[allow(deprecated)]
+pub use datetime::datetime_format_to_regex;
+pub(crate) use datetime::parse_config_datetime;
+pub use datetime::{
+    try_datetime_format_to_regex, validate_format_to_regex, InvalidDatetime, InvalidDatetimeReason,
+};
+pub use diagnostic::{Diagnostic, DiagnosticKind};
+pub use meta::MetaValue;
+pub use recurrence::Recurrence;
+pub use severity::Severity;
+pub use snooze::{parse_until, Snooze, SnoozeFile};
+pub use stream::{stream_reminders, CancelHandle, ReminderStream, ScanEvent};
+pub use trigger::{DependencyCondition, IssueState, LockfileParser, Trigger};
+pub use validate::{ValidationFailure, Validator};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Remind {
     pub datetime: i64,
+    /// The first date of a range such as `2025/01/01..2025/03/01`, from which the reminder warns.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<i64>,
+    /// The last date of a range, after which the reminder fails. `datetime` is the same date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<i64>,
+    /// The entry of `datetime_format` that parsed `datetime`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub datetime_format: Option<String>,
+    /// A date written in the comment that could not be read, e.g. `2024/02/30`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invalid_datetime: Option<InvalidDatetime>,
+    /// The relative deadline written in the comment, e.g. `+30d`, which `datetime` was resolved from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_deadline: Option<String>,
+    /// The milestone or period written in the comment, e.g. `@sprint-42` or `2025-Q3`,
+    /// which `datetime` was resolved from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period: Option<String>,
+    /// A condition written in the comment that makes the reminder due besides its date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<Trigger>,
+    /// The period written in the comment, e.g. `every 3 months`, which `datetime` was moved by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
     pub message: String,
     pub position: Position,
-    pub meta: HashMap<String, String>,
+    pub meta: HashMap<String, MetaValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blame: Option<Blame>,
+}
+
+impl Remind {
+    /// Whether the deadline has passed at `now`, a Unix timestamp, or the trigger is due.
+    /// A reminder with neither a date nor a trigger, or with a date that could not be read,
+    /// is always expired.
+    pub fn is_expired(&self, now: i64) -> bool {
+        if self.invalid_datetime.is_some() {
+            return true;
+        }
+        match &self.trigger {
+            Some(trigger) => trigger.is_due() || (self.datetime != 0 && self.datetime < now),
+            None => self.datetime < now,
+        }
+    }
+
+    /// How `run` reports the reminder at `now`: a warning within `warn_before` of the deadline
+    /// or during the `error_after` grace period, an error after it, or `None` before the window.
+    pub fn severity(&self, now: i64, config: &Config) -> Option<Severity> {
+        severity::severity(self, now, config.warn_before(), config.error_after())
+    }
+
+    /// A stable id of the reminder for `.remindsnooze`, from its file and text but not its line.
+    pub fn fingerprint(&self) -> String {
+        snooze::fingerprint(self)
+    }
+
+    /// Whether the comment gives a date or a trigger to decide when the reminder is due.
+    /// A date that could not be read counts too, so that a typo is reported instead of
+    /// dropping the reminder.
+    pub(crate) fn has_deadline(&self) -> bool {
+        self.datetime != 0 || self.trigger.is_some() || self.invalid_datetime.is_some()
+    }
+
+    /// The `${assignee}` meta of the comment, falling back to the author from `git blame`.
+    pub fn assignee(&self) -> Option<&str> {
+        self.meta
+            .get(ASSIGNEE_META)
+            .map(MetaValue::as_str)
+            .or_else(|| self.blame.as_ref().map(|b| b.author_name.as_str()))
+    }
+
+    /// Attributes the reminder to `blame`, whose author also becomes the `${assignee}` meta
+    /// when the comment has none, so that output and `validates` see the fallback too.
+    pub(crate) fn attribute(&mut self, blame: Blame) {
+        if !blame.author_name.is_empty() {
+            self.meta
+                .entry(ASSIGNEE_META.to_string())
+                .or_insert_with(|| MetaValue::Text(blame.author_name.clone()));
+        }
+        self.blame = Some(blame);
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub file: String,
     pub line: u64,
+    pub end_line: u64,
 }
 
-pub fn list_reminders(config: &Config) -> Result<Vec<Remind>, Error> {
-    let meta_regex = convert_meta_regex(config.comment_regex());
-    let matcher = RegexMatcherBuilder::new().build(&meta_regex)?;
-
-    let mut searcher_builder = SearcherBuilder::new();
-    let mut searcher = searcher_builder
-        .binary_detection(grep_searcher::BinaryDetection::quit(b'\0'))
-        .line_number(true)
-        .build();
-
-    let mut builder = WalkBuilder::new(config.search_directory());
-    let walker = builder
-        .hidden(false)
-        .add_custom_ignore_filename(config.ignore_file_path())
-        .ignore(true)
-        .parents(false)
-        .build();
-
-    let datetime = config.datetime_format().to_owned();
-    let datetime_regex = datetime_format_to_regex(&datetime);
-    let datetime_regex = RegexBuilder::new(&datetime_regex).build()?;
-
-    let mut reminds = walker
-        .filter_map(|e| {
-            let mut reminders: Vec<Remind> = vec![];
-            let entry = e.ok()?;
-            let path = line_processor(
-                &mut reminders,
-                entry.path().display().to_string(),
-                datetime.clone(),
-                &datetime_regex,
-                config.comment_regex(),
-            );
-
-            let _result = searcher.search_path(&matcher, entry.path(), path);
-            Some(reminders)
-        })
-        .flatten()
-        .collect::<Vec<_>>();
+/// The meta field `git blame` fills in when the comment names no one.
+const ASSIGNEE_META: &str = "assignee";
 
-    if !config.remind_if_no_date() {
-        reminds.retain(|r| r.datetime != 0);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Blame {
+    pub author_name: String,
+    pub author_email: String,
+    pub commit_id: String,
+    pub commit_time: i64,
+}
+
+pub fn list_reminders(config: &Config) -> Result<Reminders, Error> {
+    let mut stream = stream_reminders(config)?;
+    let mut reminds = vec![];
+    let mut diagnostics = vec![];
+    for event in stream.by_ref() {
+        match event {
+            ScanEvent::Remind(remind) => reminds.push(remind),
+            ScanEvent::Diagnostic(diagnostic) => diagnostics.push(diagnostic),
+            ScanEvent::FileVisited(_) | ScanEvent::FileSkipped(_) => {}
+        }
     }
+    stream.finish()?;
+
+    // Threads finish in arbitrary order, so merge into a stable file/line order.
+    reminds.sort_by(|a, b| {
+        a.position
+            .file
+            .cmp(&b.position.file)
+            .then(a.position.line.cmp(&b.position.line))
+    });
+    diagnostics.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
 
     if config.sort_by_deadline() {
-        reminds.sort_by(|a, b| a.datetime.cmp(&b.datetime));
+        reminds.sort_by_key(|r| r.datetime);
     }
 
-    Ok(reminds)
+    Ok(Reminders {
+        reminds,
+        diagnostics,
+    })
 }
 
-fn parse_datetime(v: &str, format: &str) -> Result<i64, Error> {
-    match v {
-        "" => Ok(0),
-        _ => {
-            let parsed_date =
-                if format.contains("%H") || format.contains("%M") || format.contains("%S") {
-                    NaiveDateTime::parse_from_str(v, format)?
-                        .and_utc()
-                        .timestamp()
-                } else {
-                    NaiveDate::parse_from_str(v, format)?
-                        .and_time(NaiveTime::default())
-                        .and_utc()
-                        .timestamp()
-                };
-            Ok(parsed_date)
-        }
+/// Extracts reminders from `text` as if it were the content of the file at `virtual_path`.
+///
+/// The path only decides the reported position and the comment syntax; nothing is read from disk.
+pub fn scan_text(config: &Config, virtual_path: &str, text: &str) -> Result<Reminders, Error> {
+    let scanner = Scanner::new(config)?;
+    let mut scan = scanner.scan_text(&mut scanner.searcher(), Path::new(virtual_path), text);
+    if scan.reminds.iter().any(|r| r.relative_deadline.is_some()) {
+        resolve_relative_deadlines(&mut scan.reminds, Repo::discover().ok().as_ref());
+    }
+    TriggerContext::new(config).evaluate(&mut scan);
+    resolve_recurrences(
+        &mut scan.reminds,
+        Utc::now().timestamp(),
+        config.recurrence_window(),
+    );
+
+    let mut reminds = scan.reminds;
+    if !config.remind_if_no_date() {
+        reminds.retain(Remind::has_deadline);
     }
-}
 
-fn line_processor<'a>(
-    reminds: &'a mut Vec<Remind>,
-    entry_path: String,
-    datetime_format: String,
-    datetime_regex: &'a Regex,
-    comment_regex: &'a str,
-) -> UTF8<impl FnMut(u64, &str) -> Result<bool, io::Error> + 'a> {
-    UTF8(move |line_num, line| {
-        let datetime_str = datetime_regex.find(line).map_or("", |m| m.as_str());
-        let parsed = parse_datetime(datetime_str, &datetime_format);
-        let datetime = parsed.unwrap_or_else(|_| {
-            eprintln!("Failed to parse datetime: {}", datetime_str);
-            0
-        });
-
-        let meta = extract_placeholders(comment_regex, line).unwrap_or_default();
-
-        reminds.push(Remind {
-            datetime,
-            message: line.trim_start().to_string(),
-            position: Position {
-                file: entry_path.clone(),
-                line: line_num,
-            },
-            meta,
-        });
-        Ok(true)
+    if config.sort_by_deadline() {
+        reminds.sort_by_key(|r| r.datetime);
+    }
+
+    Ok(Reminders {
+        reminds,
+        diagnostics: scan.diagnostics,
     })
 }
 
-pub fn datetime_format_to_regex(format: &str) -> String {
-    let mut re = format.to_string();
-    let replacements = vec![
-        ("%Y", r"\d{4}"),
-        ("%m", r"\d{2}"),
-        ("%d", r"\d{2}"),
-        ("%H", r"\d{2}"),
-        ("%M", r"\d{2}"),
-        ("%S", r"\d{2}"),
-    ];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::builder::ConfigBuilder;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_scan_text() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let text = "fn main() {}\n// remind: 2024/06/27 remove this\n// remind: no date\n";
+        let reminds = scan_text(&config, "src/main.rs", text).unwrap().reminds;
+
+        assert_eq!(reminds.len(), 1);
+        assert_eq!(reminds[0].position.file, "src/main.rs");
+        assert_eq!(reminds[0].position.line, 2);
+        assert_eq!(reminds[0].datetime, 1719446400);
+    }
+
+    #[test]
+    fn test_scan_text_invalid_datetime() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let text = "// remind: 2024/13/45 invalid month\n// remind: 2024/6/5 unpadded\n";
+        let reminders = scan_text(&config, "src/main.rs", text).unwrap();
+
+        // Kept without `remind_if_no_date`, and due whatever the date.
+        let reasons = reminders
+            .reminds
+            .iter()
+            .map(|r| r.invalid_datetime.as_ref().map(|invalid| invalid.reason))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            vec![
+                Some(InvalidDatetimeReason::ImpossibleDate),
+                Some(InvalidDatetimeReason::UnpaddedFields),
+            ]
+        );
+        assert!(reminders.reminds[0].is_expired(0));
+        assert!(reminders.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_scan_text_comment_aware() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("remind.yml");
+        std::fs::write(&config_path, "comment_aware: true\n").unwrap();
+        let config = ConfigBuilder::new()
+            .config_file_path(Some(config_path.to_string_lossy().into_owned()))
+            .build()
+            .unwrap();
+
+        let text = "let s = \"remind: 2020/01/01 str\"; // remind: 2020/03/03 after\n\
+                    let t = \"remind: 2020/01/01\";\n";
+        let reminders = scan_text(&config, "src/main.rs", text).unwrap();
+
+        assert_eq!(reminders.reminds.len(), 1);
+        assert_eq!(reminders.reminds[0].datetime, 1583193600);
+        assert!(reminders.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_scan_text_milestones_next_to_mentions() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("remind.yml");
+        std::fs::write(
+            &config_path,
+            "comment_regex: \"(@${assignee} )?remind:\"\nmilestones:\n  sprint-42: 2024-06-27\n",
+        )
+        .unwrap();
+        let config = ConfigBuilder::new()
+            .config_file_path(Some(config_path.to_string_lossy().into_owned()))
+            .remind_if_no_date(Some(true))
+            .build()
+            .unwrap();
 
-    for (fmt, rep) in replacements {
-        re = re.replace(fmt, rep);
+        let text = "// @alice remind: fix this\n\
+                    // @alice remind: @sprint-42 fix this\n\
+                    // remind: @sprint-99 fix this\n";
+        let reminders = scan_text(&config, "src/main.rs", text).unwrap();
+
+        let deadlines = reminders
+            .reminds
+            .iter()
+            .map(|r| (r.datetime, r.meta["assignee"].as_str()))
+            .take(2)
+            .collect::<Vec<_>>();
+        assert_eq!(deadlines, vec![(0, "alice"), (1719446400, "alice")]);
+        assert_eq!(
+            reminders
+                .diagnostics
+                .iter()
+                .map(|d| (d.line, d.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(Some(3), "unknown milestone `@sprint-99`")]
+        );
+    }
+
+    #[test]
+    fn test_list_reminders_in_parallel() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("src");
+        for (index, directory) in ["a", "b/c", "d"].iter().enumerate() {
+            let directory = root.join(directory);
+            std::fs::create_dir_all(&directory).unwrap();
+            for file in 0..4 {
+                let text = format!(
+                    "// remind: 2024/06/{:02} first\nfn f() {{}}\n// remind: 2020/01/01 second\n",
+                    index * 4 + file + 1
+                );
+                std::fs::write(directory.join(format!("{}.rs", file)), text).unwrap();
+            }
+        }
+        let config_path = dir.path().join("remind.yml");
+        std::fs::write(
+            &config_path,
+            format!("search_directory: {}\n", root.display()),
+        )
+        .unwrap();
+
+        let positions = |threads: usize| {
+            let config = ConfigBuilder::new()
+                .config_file_path(Some(config_path.to_string_lossy().into_owned()))
+                .threads(Some(threads))
+                .build()
+                .unwrap();
+            list_reminders(&config)
+                .unwrap()
+                .reminds
+                .into_iter()
+                .map(|r| (r.position.file, r.position.line))
+                .collect::<Vec<_>>()
+        };
+
+        // Whatever order the threads finish in, the merge is sorted by file and line.
+        let single = positions(1);
+        assert_eq!(single.len(), 24);
+        assert!(single.is_sorted());
+        assert_eq!(positions(4), single);
+    }
+
+    #[test]
+    fn test_attribute_fills_assignee() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let text = "// remind: 2024/06/27 remove this\n";
+        let mut remind = scan_text(&config, "src/main.rs", text)
+            .unwrap()
+            .reminds
+            .remove(0);
+        let blame = Blame {
+            author_name: "alice".into(),
+            author_email: "alice@example.com".into(),
+            commit_id: "0".repeat(40),
+            commit_time: 1719446400,
+        };
+
+        remind.attribute(blame.clone());
+        assert_eq!(remind.meta["assignee"], MetaValue::Text("alice".into()));
+        assert_eq!(remind.assignee(), Some("alice"));
+
+        // An assignee written in the comment wins over the author.
+        remind
+            .meta
+            .insert("assignee".into(), MetaValue::Text("bob".into()));
+        remind.attribute(blame);
+        assert_eq!(remind.assignee(), Some("bob"));
     }
 
-    re
+    #[test]
+    fn test_scan_text_diagnostics() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let text = "// remind: 2024/06/27 CST remove this\n";
+        let reminders = scan_text(&config, "src/main.rs", text).unwrap();
+        assert_eq!(reminders.reminds[0].datetime, 1719446400);
+        assert_eq!(
+            reminders.diagnostics[0].kind,
+            DiagnosticKind::UnknownTimezone
+        );
+
+        let text = "// remind: 2024/06/27 GMT+25 remove this\n";
+        let reminders = scan_text(&config, "src/main.rs", text).unwrap();
+        assert_eq!(reminders.diagnostics.len(), 1);
+        assert_eq!(reminders.reminds[0].datetime, 1719446400);
+        assert_eq!(
+            reminders.diagnostics[0].kind,
+            DiagnosticKind::UnknownTimezone
+        );
+
+        let binary = "\0\0// remind: 2024/06/27 remove this\n";
+        let reminders = scan_text(&config, "image.png", binary).unwrap();
+
+        assert_eq!(reminders.reminds.len(), 0);
+        assert_eq!(reminders.diagnostics[0].kind, DiagnosticKind::BinaryFile);
+    }
 }
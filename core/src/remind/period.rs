@@ -0,0 +1,108 @@
+use std::sync::LazyLock;
+
+use anyhow::{anyhow, Error};
+use chrono::NaiveDate;
+use regex::Regex;
+
+/// `2025-Q3` or `2025-H1` in calendar years, `FY2025`, `FY2025Q2` or `FY2025H1` in fiscal years.
+static PERIOD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:(\d{4})-([QH])(\d)|FY(\d{4})(?:([QH])(\d))?)\b").unwrap());
+
+/// `@sprint-42`, a milestone named in the config.
+static MILESTONE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[\s(])@([A-Za-z0-9][\w.\-]*)").unwrap());
+
+/// Finds every `@name` in `text` that may be a milestone, returning where its `@` is and the
+/// name as written, without the `@`.
+pub(crate) fn milestones(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    MILESTONE.captures_iter(text).filter_map(|captures| {
+        let name = captures.get(1)?;
+        Some((name.start() - 1, name.as_str().trim_end_matches(['.', '-'])))
+    })
+}
+
+/// Finds a period in `text`, returning it as written with the day right after it ends.
+///
+/// Fiscal years start in the month `fiscal_year_start` and are named after the calendar year
+/// they start in, so with April, `FY2025H1` runs from 2025/04/01 to 2025/09/30.
+pub(crate) fn period_end(
+    text: &str,
+    fiscal_year_start: u32,
+) -> Option<Result<(String, NaiveDate), Error>> {
+    let captures = PERIOD.captures(text)?;
+    let written = captures[0].to_string();
+
+    let (year, start_month, part) = match captures.get(1) {
+        Some(year) => (year, 1, captures.get(2).zip(captures.get(3))),
+        None => (
+            captures.get(4)?,
+            fiscal_year_start,
+            captures.get(5).zip(captures.get(6)),
+        ),
+    };
+    let year: i32 = year.as_str().parse().ok()?;
+
+    let (months, index) = match part.map(|(kind, n)| (kind.as_str(), n.as_str())) {
+        None => (12, 1),
+        Some(("Q", n @ ("1" | "2" | "3" | "4"))) => (3, n.parse().ok()?),
+        Some(("H", n @ ("1" | "2"))) => (6, n.parse().ok()?),
+        Some(_) => return Some(Err(anyhow!("`{}` is not a valid period", written))),
+    };
+
+    // Months since year 0, so that periods can run over the end of a calendar year.
+    let end = year * 12 + (start_month as i32 - 1) + index * months;
+    let end = NaiveDate::from_ymd_opt(end.div_euclid(12), end.rem_euclid(12) as u32 + 1, 1)?;
+    Some(Ok((written, end)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn end(text: &str, fiscal_year_start: u32) -> Option<NaiveDate> {
+        period_end(text, fiscal_year_start).map(|r| r.unwrap().1)
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    #[test]
+    fn test_period_end() {
+        assert_eq!(end("// remind: 2025-Q3 drop it", 1), date(2025, 10, 1));
+        assert_eq!(end("// remind: 2025-Q4", 4), date(2026, 1, 1));
+        assert_eq!(end("// remind: 2025-H1", 1), date(2025, 7, 1));
+
+        assert_eq!(end("// remind: FY2025H1", 4), date(2025, 10, 1));
+        assert_eq!(end("// remind: FY2025Q4", 4), date(2026, 4, 1));
+        assert_eq!(end("// remind: FY2025", 10), date(2026, 10, 1));
+        assert_eq!(end("// remind: FY2025", 1), date(2026, 1, 1));
+
+        assert!(period_end("// remind: 2025-Q5", 1).unwrap().is_err());
+        assert!(period_end("// remind: FY2025H3", 1).unwrap().is_err());
+        assert_eq!(end("// remind: 2025/06/27", 1), None);
+    }
+
+    fn milestone(text: &str) -> Option<&str> {
+        milestones(text).next().map(|(_, name)| name)
+    }
+
+    #[test]
+    fn test_milestone() {
+        assert_eq!(
+            milestone("// remind: @sprint-42 drop it"),
+            Some("sprint-42")
+        );
+        assert_eq!(
+            milestone("// remind: (@release-2025-spring)"),
+            Some("release-2025-spring")
+        );
+        assert_eq!(milestone("// remind: by @sprint-42."), Some("sprint-42"));
+        assert_eq!(milestone("// remind: mail me@example.com"), None);
+        assert_eq!(
+            milestones("// @alice remind: @sprint-42").collect::<Vec<_>>(),
+            vec![(3, "alice"), (18, "sprint-42")]
+        );
+    }
+}
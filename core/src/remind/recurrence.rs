@@ -0,0 +1,141 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::relative::after;
+use super::Remind;
+
+/// `every 3 months`, `every 90 days` or `every week`, next to the date the recurrence starts from.
+static RECURRENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bevery\s+(?:(\d+)\s*)?(day|week|month|year)s?\b").unwrap());
+
+/// A reminder that comes back every `every` after `anchor`, the date written in the comment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recurrence {
+    /// The period in the units of relative deadlines, e.g. `3m` for `every 3 months`.
+    pub every: String,
+    pub anchor: i64,
+    /// The first occurrence after now, decided on every run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<i64>,
+}
+
+/// Finds a recurrence in `text`, counting from `anchor`.
+pub(crate) fn recurrence(text: &str, anchor: i64) -> Option<Recurrence> {
+    let captures = RECURRENCE.captures(text)?;
+    let amount: u32 = captures
+        .get(1)
+        .map_or(Some(1), |n| n.as_str().parse().ok())?;
+    if amount == 0 {
+        return None;
+    }
+    let unit = &captures[2][..1];
+
+    Some(Recurrence {
+        every: format!("{}{}", amount, unit),
+        anchor,
+        next: None,
+    })
+}
+
+impl Recurrence {
+    /// The `n`th occurrence after the anchor. Each one counts from the anchor, so monthly
+    /// reminders from the 31st stay on the last day of shorter months instead of drifting.
+    fn occurrence(&self, n: u32) -> Option<i64> {
+        let split = self.every.len() - 1;
+        let (amount, unit) = self.every.split_at(split);
+        let amount: u32 = amount.parse().ok()?;
+        after(self.anchor, &format!("{}{}", amount.checked_mul(n)?, unit))
+    }
+}
+
+/// Moves the deadline of every recurring reminder to its occurrence at `now`.
+///
+/// A reminder is due from each occurrence for `window`, or until the next occurrence when the
+/// window is empty, so it keeps firing until the anchor date in the comment is bumped.
+/// Outside the window its deadline is the next occurrence.
+pub(crate) fn resolve_recurrences(reminds: &mut [Remind], now: i64, window: &str) {
+    for remind in reminds.iter_mut() {
+        let Some(recurrence) = &mut remind.recurrence else {
+            continue;
+        };
+
+        let mut latest = None;
+        let mut next = None;
+        for n in 1.. {
+            match recurrence.occurrence(n) {
+                Some(occurrence) if occurrence <= now => latest = Some(occurrence),
+                occurrence => {
+                    next = occurrence;
+                    break;
+                }
+            }
+        }
+
+        let firing = latest.filter(|&occurrence| match window {
+            "" => true,
+            window => after(occurrence, window).is_some_and(|end| now < end),
+        });
+        recurrence.next = next;
+        if let Some(deadline) = firing.or(next) {
+            remind.datetime = deadline;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::builder::ConfigBuilder;
+    use crate::remind::scan_text;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_recurrence() {
+        let every = |text| recurrence(text, 0).map(|r| r.every);
+
+        assert_eq!(
+            every("// remind: every 3 months from 2024/01/01"),
+            Some("3m".into())
+        );
+        assert_eq!(
+            every("// remind: every 90 days from 2024/01/01"),
+            Some("90d".into())
+        );
+        assert_eq!(
+            every("// remind: every week from 2024/01/01"),
+            Some("1w".into())
+        );
+        assert_eq!(every("// remind: everyday 2024/01/01"), None);
+        assert_eq!(every("// remind: every 0 days"), None);
+    }
+
+    #[test]
+    fn test_resolve_recurrences() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let text = "// remind: every 1 month from 2024/01/31 rotate the key\n";
+        let scanned = scan_text(&config, "src/main.rs", text).unwrap().reminds;
+        let anchor = scanned[0].recurrence.as_ref().unwrap().anchor;
+        // 2024-01-31, 2024-02-29 and 2024-03-31
+        assert_eq!(anchor, 1706659200);
+        let (february, march) = (1709164800, 1711843200);
+        let day = 86400;
+
+        // Without a window, the reminder stays due from the latest occurrence on.
+        let mut reminds = scanned.clone();
+        resolve_recurrences(&mut reminds, february + 10 * day, "");
+        assert_eq!(reminds[0].datetime, february);
+        assert_eq!(reminds[0].recurrence.as_ref().unwrap().next, Some(march));
+
+        // After the window, the deadline moves to the next occurrence.
+        let mut reminds = scanned.clone();
+        resolve_recurrences(&mut reminds, february + 10 * day, "7d");
+        assert_eq!(reminds[0].datetime, march);
+
+        // Before the first occurrence, the deadline is the first occurrence.
+        let mut reminds = scanned;
+        resolve_recurrences(&mut reminds, anchor + day, "7d");
+        assert_eq!(reminds[0].datetime, february);
+    }
+}
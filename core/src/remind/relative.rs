@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+use std::time::UNIX_EPOCH;
+
+use chrono::{DateTime, Days, Months, Utc};
+use regex::Regex;
+
+use super::Remind;
+use crate::git::blame::line_commit_times;
+use crate::git::Repo;
+
+/// A deadline relative to when the comment was written, e.g. `+30d`, `+2w`, `+3m` or `+1y`.
+static RELATIVE_DEADLINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[\s(])(\+(\d+)([dwmy]))\b").unwrap());
+
+/// A duration in the config, e.g. `14d`, in the same units as relative deadlines.
+static DURATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d+[dwmy]$").unwrap());
+
+/// Finds a relative deadline in `text`, returning the expression as written.
+pub(crate) fn relative_deadline(text: &str) -> Option<String> {
+    RELATIVE_DEADLINE
+        .captures(text)
+        .map(|captures| captures[1].to_string())
+}
+
+/// Resolves `expression` against `anchor`, both as Unix timestamps.
+fn resolve(expression: &str, anchor: i64) -> Option<i64> {
+    let captures = RELATIVE_DEADLINE.captures(expression)?;
+    let amount: u32 = captures[2].parse().ok()?;
+    let anchor = DateTime::<Utc>::from_timestamp(anchor, 0)?;
+
+    let deadline = match &captures[3] {
+        "d" => anchor.checked_add_days(Days::new(amount.into())),
+        "w" => anchor.checked_add_days(Days::new(u64::from(amount) * 7)),
+        "m" => anchor.checked_add_months(Months::new(amount)),
+        "y" => anchor.checked_add_months(Months::new(amount.checked_mul(12)?)),
+        _ => None,
+    };
+    deadline.map(|d| d.timestamp())
+}
+
+/// The timestamp `duration` after `timestamp`, or `None` if `duration` is not like `14d`.
+pub(crate) fn after(timestamp: i64, duration: &str) -> Option<i64> {
+    match DURATION.is_match(duration) {
+        true => resolve(&format!("+{}", duration), timestamp),
+        false => None,
+    }
+}
+
+/// Turns the relative deadlines of `reminds` into absolute ones.
+///
+/// Each deadline counts from the commit that last touched the line, falling back to the mtime
+/// of the file for lines that are not committed, and to now for text that is not on disk.
+pub(crate) fn resolve_relative_deadlines(reminds: &mut [Remind], repo: Option<&Repo>) {
+    let mut by_file: BTreeMap<String, Vec<&mut Remind>> = BTreeMap::new();
+    for remind in reminds.iter_mut() {
+        if remind.datetime == 0 && remind.relative_deadline.is_some() {
+            by_file
+                .entry(remind.position.file.clone())
+                .or_default()
+                .push(remind);
+        }
+    }
+
+    for (file, reminds) in by_file {
+        let lines = reminds.iter().map(|r| r.position.line).collect::<Vec<_>>();
+        let commit_times = repo
+            .map(|repo| line_commit_times(repo, &file, &lines))
+            .unwrap_or_default();
+        let fallback = std::fs::metadata(&file)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or_else(|| Utc::now().timestamp(), |d| d.as_secs() as i64);
+
+        for remind in reminds {
+            let anchor = commit_times
+                .get(&remind.position.line)
+                .copied()
+                .unwrap_or(fallback);
+            let expression = remind.relative_deadline.as_deref().unwrap_or_default();
+            if let Some(deadline) = resolve(expression, anchor) {
+                remind.datetime = deadline;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_relative_deadline() {
+        let deadline = relative_deadline("// remind: +30d drop this shim");
+        assert_eq!(deadline.as_deref(), Some("+30d"));
+
+        let deadline = relative_deadline("// remind: (+2w) drop this shim");
+        assert_eq!(deadline.as_deref(), Some("+2w"));
+
+        assert_eq!(relative_deadline("// remind: x+30d"), None);
+        assert_eq!(relative_deadline("// remind: +30days"), None);
+        assert_eq!(relative_deadline("// remind: 2024/06/27"), None);
+    }
+
+    #[test]
+    fn test_resolve() {
+        // 2024-01-31T00:00:00Z
+        let anchor = 1706659200;
+
+        assert_eq!(resolve("+30d", anchor), Some(anchor + 30 * 86400));
+        assert_eq!(resolve("+2w", anchor), Some(anchor + 14 * 86400));
+        // Months are calendar months, clamped to the end of a shorter month (2024-02-29).
+        assert_eq!(resolve("+1m", anchor), Some(1709164800));
+        // 2025-01-31
+        assert_eq!(resolve("+1y", anchor), Some(1738281600));
+    }
+
+    #[test]
+    fn test_after() {
+        assert_eq!(after(0, "14d"), Some(14 * 86400));
+        assert_eq!(after(0, "0d"), Some(0));
+        assert_eq!(after(0, "+14d"), None);
+        assert_eq!(after(0, "14 days"), None);
+    }
+}
@@ -0,0 +1,488 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
+
+use anyhow::Error;
+use grep_regex::{RegexMatcher, RegexMatcherBuilder};
+use grep_searcher::{BinaryDetection, Searcher, SearcherBuilder, Sink, SinkError, SinkMatch};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::comment::{comment_ranges, match_in_comment, syntax_for};
+use super::continuation::continuation_lines;
+use super::datetime::{parse_line_datetime, DatetimeMatcher, InvalidDatetime};
+use super::diagnostic::{Diagnostic, DiagnosticKind};
+use super::meta::{convert_meta_regex, MetaMatcher, MetaValue};
+use super::period::{milestones, period_end};
+use super::recurrence::recurrence;
+use super::relative::relative_deadline;
+use super::trigger::parse_trigger;
+use super::{Position, Remind};
+use crate::config::Config;
+
+/// Extracts reminders from a single file, with the patterns compiled once per run.
+pub(crate) struct Scanner {
+    config: Config,
+    matcher: RegexMatcher,
+    comment_regex: Regex,
+    datetime_matchers: Vec<DatetimeMatcher>,
+    meta_matcher: MetaMatcher,
+}
+
+/// What a single file yields: its reminders and the problems found on the way.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct FileScan {
+    pub(crate) reminds: Vec<Remind>,
+    #[serde(default)]
+    pub(crate) diagnostics: Vec<Diagnostic>,
+}
+
+impl Scanner {
+    pub(crate) fn new(config: &Config) -> Result<Self, Error> {
+        let meta_regex = convert_meta_regex(config.comment_regex(), config.datetime_formats())?;
+        let matcher = RegexMatcherBuilder::new().build(&meta_regex)?;
+        let comment_regex = Regex::new(&meta_regex)?;
+        let datetime_matchers = config
+            .datetime_formats()
+            .iter()
+            .map(|format| DatetimeMatcher::new(format))
+            .collect::<Result<Vec<_>, _>>()?;
+        let meta_matcher = MetaMatcher::new(
+            config.comment_regex(),
+            config.datetime_formats(),
+            config.timezone(),
+        )?;
+
+        Ok(Self {
+            config: config.clone(),
+            matcher,
+            comment_regex,
+            datetime_matchers,
+            meta_matcher,
+        })
+    }
+
+    pub(crate) fn searcher(&self) -> Searcher {
+        SearcherBuilder::new()
+            .binary_detection(BinaryDetection::quit(b'\0'))
+            .line_number(true)
+            .build()
+    }
+
+    pub(crate) fn scan_path(&self, searcher: &mut Searcher, path: &Path) -> FileScan {
+        let mut scan = FileScan::default();
+        let file = path.display().to_string();
+        let sink = LineSink::new(self, &file, &mut scan);
+        if let Err(e) = searcher.search_path(&self.matcher, path, sink) {
+            let diagnostic = Diagnostic::new(DiagnosticKind::SearchError, Some(file), None, e);
+            return FileScan {
+                reminds: vec![],
+                diagnostics: vec![diagnostic],
+            };
+        }
+
+        if self.needs_text() && !scan.reminds.is_empty() {
+            if let Ok(text) = std::fs::read_to_string(path) {
+                self.refine(&mut scan, path, &text);
+            }
+        }
+
+        scan
+    }
+
+    /// Scans `text` as if it were the content of the file at `path`.
+    pub(crate) fn scan_text(&self, searcher: &mut Searcher, path: &Path, text: &str) -> FileScan {
+        let mut scan = FileScan::default();
+        let file = path.display().to_string();
+        let sink = LineSink::new(self, &file, &mut scan);
+        if let Err(e) = searcher.search_slice(&self.matcher, text.as_bytes(), sink) {
+            let diagnostic = Diagnostic::new(DiagnosticKind::SearchError, Some(file), None, e);
+            return FileScan {
+                reminds: vec![],
+                diagnostics: vec![diagnostic],
+            };
+        }
+
+        if self.needs_text() && !scan.reminds.is_empty() {
+            self.refine(&mut scan, path, text);
+        }
+
+        scan
+    }
+
+    /// Builds the reminder on `line`, reading its date, meta and conditions from `text`, the
+    /// part of the line from the match of `comment_regex` that counts.
+    fn remind(
+        &self,
+        line: &str,
+        text: &str,
+        position: Position,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Remind {
+        let parsed = self.datetime(text, &position, diagnostics);
+        let (start, deadline) = parsed.range();
+        let Datetime {
+            datetime,
+            format: datetime_format,
+            invalid: invalid_datetime,
+            ..
+        } = parsed;
+        let (datetime, period) = match datetime {
+            0 => self
+                .period(text, &position, diagnostics)
+                .map_or((0, None), |(deadline, period)| (deadline, Some(period))),
+            datetime => (datetime, None),
+        };
+        let meta = self.meta(text, &position, diagnostics);
+
+        // Relative deadlines are resolved after the scan, as they depend on the git history.
+        let relative_deadline = match datetime {
+            0 => relative_deadline(text),
+            _ => None,
+        };
+
+        // The date of a recurring reminder is its anchor; the occurrence depends on the run.
+        let recurrence = match datetime {
+            0 => None,
+            _ => recurrence(text, datetime),
+        };
+
+        Remind {
+            datetime,
+            start,
+            deadline,
+            datetime_format,
+            invalid_datetime,
+            relative_deadline,
+            period,
+            // Only the condition is cached; whether it is due is decided on every run.
+            trigger: parse_trigger(text),
+            recurrence,
+            message: line.trim_start().to_string(),
+            position,
+            meta,
+            blame: None,
+        }
+    }
+
+    /// Parses the date or range of a reminder with the format that matched it, or the reason a
+    /// date that looks like one of `datetime_format` cannot be read. Other dates in the text are
+    /// reported rather than silently ignored.
+    fn datetime(
+        &self,
+        text: &str,
+        position: &Position,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Datetime {
+        match parse_line_datetime(text, &self.datetime_matchers, self.config.timezone()) {
+            Ok(Some(parsed)) => {
+                if let Some(zone) = &parsed.unknown_zone {
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticKind::UnknownTimezone,
+                        Some(position.file.clone()),
+                        Some(position.line),
+                        format!(
+                            "`{}` is not a known zone, so the date is read in {}",
+                            zone,
+                            self.config.timezone()
+                        ),
+                    ));
+                }
+                if !parsed.others.is_empty() {
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticKind::MultipleDates,
+                        Some(position.file.clone()),
+                        Some(position.line),
+                        format!(
+                            "the first date is the deadline, also found `{}`; write a range as `start..end`",
+                            parsed.others.join("`, `")
+                        ),
+                    ));
+                }
+                Datetime {
+                    datetime: parsed.datetime,
+                    format: Some(parsed.format.to_string()),
+                    start: parsed.start,
+                    invalid: None,
+                }
+            }
+            Ok(None) => Datetime::default(),
+            Err(invalid) => Datetime {
+                invalid: Some(invalid),
+                ..Datetime::default()
+            },
+        }
+    }
+
+    /// Resolves a milestone or period written instead of a date, returning the deadline with
+    /// the milestone or period as written.
+    fn period(
+        &self,
+        text: &str,
+        position: &Position,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<(i64, String)> {
+        let mut invalid = |message: String| {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::InvalidDatetime,
+                Some(position.file.clone()),
+                Some(position.line),
+                message,
+            ));
+            None
+        };
+
+        // Without milestones in the config, `@` is left to mentions.
+        let configured = self.config.milestones();
+        if !configured.is_empty() {
+            let written = milestones(text).collect::<Vec<_>>();
+            let known = written
+                .iter()
+                .find_map(|(_, name)| Some((*configured.get(&name.to_lowercase())?, name)));
+            if let Some((deadline, name)) = known {
+                return Some((deadline, format!("@{}", name)));
+            }
+
+            // An unknown name right after the marker, where the deadline goes, is most likely a
+            // misspelt milestone. Anywhere else, such as `@${assignee}`, it is a mention.
+            let marker_end = self.comment_regex.find(text).map(|m| m.end());
+            let misspelt = written.iter().find(|(start, _)| {
+                marker_end.is_some_and(|end| end <= *start && text[end..*start].trim().is_empty())
+            });
+            if let Some((_, name)) = misspelt {
+                return invalid(format!("unknown milestone `@{}`", name));
+            }
+        }
+
+        match period_end(text, self.config.fiscal_year_start())? {
+            Ok((written, end)) => match self.config.timezone().timestamp(end.into()) {
+                Ok(deadline) => Some((deadline, written)),
+                Err(e) => invalid(e.to_string()),
+            },
+            Err(e) => invalid(e.to_string()),
+        }
+    }
+
+    /// Reads the meta placeholders of `comment_regex`, reporting typed values that cannot be
+    /// read.
+    fn meta(
+        &self,
+        text: &str,
+        position: &Position,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> HashMap<String, MetaValue> {
+        let mut problems = vec![];
+        let meta = self.meta_matcher.extract(text, &mut problems);
+        diagnostics.extend(problems.into_iter().map(|problem| {
+            Diagnostic::new(
+                DiagnosticKind::InvalidMeta,
+                Some(position.file.clone()),
+                Some(position.line),
+                problem,
+            )
+        }));
+        meta
+    }
+
+    fn needs_text(&self) -> bool {
+        self.config.comment_aware() || self.joins_lines()
+    }
+
+    fn joins_lines(&self) -> bool {
+        self.config.multiline() || !self.config.continuation_marker().is_empty()
+    }
+
+    /// Applies the steps that need the whole file: comment awareness and continuation lines.
+    fn refine(&self, scan: &mut FileScan, path: &Path, text: &str) {
+        let config = &self.config;
+        let syntax = syntax_for(path, config.comment_syntaxes());
+        let ranges = syntax.as_ref().map(|s| comment_ranges(text, s));
+        let lines = lines_with_offsets(text);
+
+        // Where each reminder starts on its line. Files without a known comment syntax keep
+        // matching anywhere on a line.
+        let mut starts = vec![0; scan.reminds.len()];
+        if let (true, Some(ranges)) = (config.comment_aware(), &ranges) {
+            starts.clear();
+            let reminds = std::mem::take(&mut scan.reminds);
+            let mut diagnostics = Vec::new();
+            let mut kept_lines = HashSet::new();
+
+            for remind in reminds {
+                let Some((line_start, line)) = lines.get(remind.position.line as usize - 1) else {
+                    continue;
+                };
+                let Some(start) = match_in_comment(&self.comment_regex, line, *line_start, ranges)
+                else {
+                    continue;
+                };
+                if start == 0 {
+                    kept_lines.insert(remind.position.line);
+                    scan.reminds.push(remind);
+                } else {
+                    // The match in a comment does not start the line, as the comment follows
+                    // code or leading text, which may hold an earlier match such as one in a
+                    // string literal. The reminder is read again from the match in the comment,
+                    // so that nothing before it supplies its date or meta.
+                    let position = remind.position;
+                    scan.reminds.push(self.remind(
+                        line,
+                        &line[start..],
+                        position,
+                        &mut diagnostics,
+                    ));
+                }
+                starts.push(start);
+            }
+
+            // Dates outside comments were never reminders, so their problems are not reported.
+            scan.diagnostics
+                .retain(|d| d.line.is_none_or(|line| kept_lines.contains(&line)));
+            scan.diagnostics.append(&mut diagnostics);
+        }
+
+        if self.joins_lines() {
+            for (remind, start) in scan.reminds.iter_mut().zip(starts) {
+                let continued = continuation_lines(
+                    remind.position.line,
+                    &lines,
+                    ranges.as_deref(),
+                    syntax.as_ref(),
+                    &self.comment_regex,
+                    config.multiline(),
+                    config.continuation_marker(),
+                );
+                let head = lines
+                    .get(remind.position.line as usize - 1)
+                    .map_or("", |(_, line)| &line[start..]);
+                self.append_continuation(remind, head, continued, &mut scan.diagnostics);
+            }
+        }
+    }
+
+    /// Joins continuation lines into the reminder, picking up a date or meta they carry.
+    /// `head` is the part of the first line the reminder is read from.
+    fn append_continuation(
+        &self,
+        remind: &mut Remind,
+        head: &str,
+        continued: Vec<String>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        if continued.is_empty() {
+            return;
+        }
+
+        remind.position.end_line = remind.position.line + continued.len() as u64;
+        remind.message = format!("{} {}", remind.message.trim_end(), continued.join(" "));
+        let text = format!("{} {}", head.trim_end(), continued.join(" "));
+
+        if remind.datetime == 0 && remind.invalid_datetime.is_none() {
+            let parsed = self.datetime(&text, &remind.position, diagnostics);
+            (remind.start, remind.deadline) = parsed.range();
+            remind.datetime = parsed.datetime;
+            remind.datetime_format = parsed.format;
+            remind.invalid_datetime = parsed.invalid;
+        }
+        if remind.datetime == 0 {
+            if let Some((deadline, period)) = self.period(&text, &remind.position, diagnostics) {
+                (remind.datetime, remind.period) = (deadline, Some(period));
+            }
+        }
+        if remind.datetime == 0 && remind.relative_deadline.is_none() {
+            remind.relative_deadline = relative_deadline(&text);
+        }
+        if remind.trigger.is_none() {
+            remind.trigger = parse_trigger(&text);
+        }
+        if remind.datetime != 0 && remind.recurrence.is_none() {
+            remind.recurrence = recurrence(&text, remind.datetime);
+        }
+        for (name, value) in self.meta(&text, &remind.position, diagnostics) {
+            remind.meta.entry(name).or_insert(value);
+        }
+    }
+}
+
+/// The date found in the text of a reminder.
+#[derive(Default)]
+struct Datetime {
+    datetime: i64,
+    format: Option<String>,
+    start: Option<i64>,
+    invalid: Option<InvalidDatetime>,
+}
+
+impl Datetime {
+    /// The start and deadline of a range, or `None` for a single date.
+    fn range(&self) -> (Option<i64>, Option<i64>) {
+        match self.start {
+            Some(start) => (Some(start), Some(self.datetime)),
+            None => (None, None),
+        }
+    }
+}
+
+/// Turns every matched line into a reminder and reports binary files instead of dropping them.
+struct LineSink<'s> {
+    scanner: &'s Scanner,
+    file: &'s str,
+    scan: &'s mut FileScan,
+}
+
+impl<'s> LineSink<'s> {
+    fn new(scanner: &'s Scanner, file: &'s str, scan: &'s mut FileScan) -> Self {
+        Self {
+            scanner,
+            file,
+            scan,
+        }
+    }
+}
+
+impl Sink for LineSink<'_> {
+    type Error = io::Error;
+
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, io::Error> {
+        let line = std::str::from_utf8(mat.bytes()).map_err(io::Error::error_message)?;
+        let line_num = mat
+            .line_number()
+            .ok_or_else(|| io::Error::error_message("line numbers not enabled"))?;
+
+        let position = Position {
+            file: self.file.to_string(),
+            line: line_num,
+            end_line: line_num,
+        };
+        let remind = self
+            .scanner
+            .remind(line, line, position, &mut self.scan.diagnostics);
+        self.scan.reminds.push(remind);
+        Ok(true)
+    }
+
+    fn binary_data(
+        &mut self,
+        _searcher: &Searcher,
+        binary_byte_offset: u64,
+    ) -> Result<bool, io::Error> {
+        self.scan.diagnostics.push(Diagnostic::new(
+            DiagnosticKind::BinaryFile,
+            Some(self.file.to_string()),
+            None,
+            format!("skipped at a NUL byte at offset {}", binary_byte_offset),
+        ));
+        Ok(false)
+    }
+}
+
+pub(crate) fn lines_with_offsets(text: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+            (start, line)
+        })
+        .collect()
+}
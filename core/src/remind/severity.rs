@@ -0,0 +1,155 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use anyhow::{anyhow, Error};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::relative::after;
+use super::Remind;
+
+/// A `[warning]` or `[error]` tag in the message of a reminder.
+static SEVERITY_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(warning|error)\]").unwrap());
+
+/// How a reminder is reported. Only errors fail `run`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl FromStr for Severity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s.to_ascii_lowercase().as_str() {
+            "warning" | "warn" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            _ => Err(anyhow!("unknown severity `{}`", s)),
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// The severity written on the reminder itself, as a `${severity}` meta or a `[warning]` tag.
+fn severity_override(remind: &Remind) -> Option<Severity> {
+    let meta = remind
+        .meta
+        .get("severity")
+        .and_then(|s| s.as_str().parse().ok());
+    meta.or_else(|| {
+        SEVERITY_TAG
+            .captures(&remind.message)
+            .and_then(|captures| captures[1].parse().ok())
+    })
+}
+
+/// The severity of `remind` at `now`, or `None` while it is not due within `warn_before` or
+/// the range it starts.
+///
+/// An expired reminder is an error once `error_after` has passed since its deadline, unless the
+/// reminder sets its own severity.
+pub(crate) fn severity(
+    remind: &Remind,
+    now: i64,
+    warn_before: &str,
+    error_after: &str,
+) -> Option<Severity> {
+    if remind.is_expired(now) {
+        if let Some(severity) = severity_override(remind) {
+            return Some(severity);
+        }
+        // A due trigger has no date to count a grace period from.
+        let in_grace = remind.datetime != 0
+            && remind.datetime < now
+            && after(remind.datetime, error_after).is_some_and(|end| end > now);
+        return Some(match in_grace {
+            true => Severity::Warning,
+            false => Severity::Error,
+        });
+    }
+
+    // A range warns from its start, besides `warn_before` its deadline.
+    let warned = remind.start.is_some_and(|start| start <= now)
+        || remind.datetime != 0
+            && after(now, warn_before).is_some_and(|end| end >= remind.datetime);
+    warned.then_some(Severity::Warning)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::builder::ConfigBuilder;
+    use crate::remind::scan_text;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_severity_override() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let text = "// remind: 2024/06/27 [warning] flaky\n// remind: 2024/06/27 remove\n";
+        let reminds = scan_text(&config, "src/main.rs", text).unwrap().reminds;
+
+        assert_eq!(severity_override(&reminds[0]), Some(Severity::Warning));
+        assert_eq!(severity_override(&reminds[1]), None);
+    }
+
+    #[test]
+    fn test_severity() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let text = "// remind: 2024/06/27 remove\n";
+        let remind = &scan_text(&config, "src/main.rs", text).unwrap().reminds[0];
+        let day = 86400;
+
+        // Without `warn_before` and `error_after`, only expired reminders are reported.
+        assert_eq!(severity(remind, remind.datetime - day, "", ""), None);
+        assert_eq!(
+            severity(remind, remind.datetime + 1, "", ""),
+            Some(Severity::Error)
+        );
+
+        assert_eq!(
+            severity(remind, remind.datetime - 15 * day, "14d", ""),
+            None
+        );
+        assert_eq!(
+            severity(remind, remind.datetime - 14 * day, "14d", ""),
+            Some(Severity::Warning)
+        );
+        assert_eq!(
+            severity(remind, remind.datetime + day, "14d", "2d"),
+            Some(Severity::Warning)
+        );
+        assert_eq!(
+            severity(remind, remind.datetime + 2 * day, "14d", "2d"),
+            Some(Severity::Error)
+        );
+    }
+
+    #[test]
+    fn test_severity_range() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let text = "// remind: 2025/01/01..2025/03/01 migrate\n";
+        let remind = &scan_text(&config, "src/main.rs", text).unwrap().reminds[0];
+        let start = remind.start.unwrap();
+        let deadline = remind.deadline.unwrap();
+        assert_eq!(remind.datetime, deadline);
+
+        assert_eq!(severity(remind, start - 1, "", ""), None);
+        assert_eq!(severity(remind, start, "", ""), Some(Severity::Warning));
+        assert_eq!(
+            severity(remind, deadline + 1, "", ""),
+            Some(Severity::Error)
+        );
+    }
+}
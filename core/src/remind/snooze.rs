@@ -0,0 +1,209 @@
+use std::fs;
+use std::io::ErrorKind;
+
+use anyhow::{anyhow, Error};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::hash::fnv1a;
+use super::Remind;
+use crate::config::timezone::Timezone;
+
+const UNTIL_FORMAT: &str = "%Y-%m-%d";
+
+/// Postpones one reminder until a day, without editing its source.
+///
+/// A snooze matches a reminder by fingerprint, which survives the comment moving to another
+/// line, or else by file and line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snooze {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+    /// The day the snooze ends, as `YYYY-MM-DD` in the configured timezone.
+    pub until: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reason: String,
+}
+
+impl Snooze {
+    pub fn matches(&self, remind: &Remind) -> bool {
+        match (&self.fingerprint, &self.file, self.line) {
+            (Some(fingerprint), _, _) => *fingerprint == remind.fingerprint(),
+            (None, Some(file), Some(line)) => {
+                normalize_path(file) == normalize_path(&remind.position.file)
+                    && (remind.position.line..=remind.position.end_line).contains(&line)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the snooze still holds at `now`, a Unix timestamp.
+    pub fn is_active(&self, now: i64, timezone: Timezone) -> bool {
+        parse_until(&self.until)
+            .ok()
+            .and_then(|until| timezone.timestamp(until.and_time(Default::default())).ok())
+            .is_some_and(|until| now < until)
+    }
+
+    /// Where the snooze points, for messages: the file and line, or else the fingerprint.
+    pub fn target(&self) -> String {
+        match (&self.file, self.line, &self.fingerprint) {
+            (Some(file), Some(line), _) => format!("{}:{}", file, line),
+            (_, _, Some(fingerprint)) => fingerprint.clone(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// The entries of a snooze file such as `.remindsnooze`, stored as TOML `[[snooze]]` tables.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnoozeFile {
+    #[serde(default, rename = "snooze")]
+    pub entries: Vec<Snooze>,
+}
+
+impl SnoozeFile {
+    /// Reads the snooze file at `path`. A missing file has no entries.
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let file: SnoozeFile =
+            toml::from_str(&content).map_err(|e| anyhow!("invalid `{}`: {}", path, e))?;
+
+        for entry in &file.entries {
+            if entry.fingerprint.is_none() && (entry.file.is_none() || entry.line.is_none()) {
+                return Err(anyhow!(
+                    "invalid `{}`: every entry needs a fingerprint, or a file and a line",
+                    path
+                ));
+            }
+            parse_until(&entry.until).map_err(|e| anyhow!("invalid `{}`: {}", path, e))?;
+        }
+
+        Ok(file)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Adds `snooze`, replacing any entry for the same reminder.
+    pub fn insert(&mut self, snooze: Snooze) {
+        self.entries
+            .retain(|entry| entry.fingerprint.is_none() || entry.fingerprint != snooze.fingerprint);
+        self.entries.push(snooze);
+    }
+
+    /// The active snooze for `remind` at `now`, if any.
+    pub fn snoozed(&self, remind: &Remind, now: i64, timezone: Timezone) -> Option<&Snooze> {
+        self.entries
+            .iter()
+            .find(|entry| entry.matches(remind) && entry.is_active(now, timezone))
+    }
+
+    /// The entries that match none of `reminds`.
+    pub fn stale<'a>(&'a self, reminds: &[Remind]) -> Vec<&'a Snooze> {
+        self.entries
+            .iter()
+            .filter(|entry| !reminds.iter().any(|remind| entry.matches(remind)))
+            .collect()
+    }
+}
+
+/// Checks that `until` is a `YYYY-MM-DD` date.
+pub fn parse_until(until: &str) -> Result<NaiveDate, Error> {
+    NaiveDate::parse_from_str(until, UNTIL_FORMAT).map_err(|e| {
+        anyhow!(
+            "`until` must be a date like 2025-01-31, got `{}`: {}",
+            until,
+            e
+        )
+    })
+}
+
+/// Paths are reported as `./src/main.rs` by a scan but usually written as `src/main.rs`.
+fn normalize_path(path: &str) -> &str {
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+/// A fingerprint of the file and text of a reminder, which stays the same when lines are
+/// inserted above it.
+pub(crate) fn fingerprint(remind: &Remind) -> String {
+    let message = remind
+        .message
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let key = format!("{}\n{}", normalize_path(&remind.position.file), message);
+    format!("{:016x}", fnv1a(key.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::builder::ConfigBuilder;
+    use crate::remind::scan_text;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_fingerprint_survives_moves() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let text = "// remind: 2024/06/27 remove\n";
+        let moved = "fn main() {}\n\n// remind:   2024/06/27 remove\n";
+        let remind = &scan_text(&config, "src/main.rs", text).unwrap().reminds[0];
+        let moved = &scan_text(&config, "./src/main.rs", moved).unwrap().reminds[0];
+
+        assert_eq!(remind.fingerprint(), moved.fingerprint());
+        assert_eq!(remind.fingerprint().len(), 16);
+    }
+
+    #[test]
+    fn test_snooze_file() {
+        let config = ConfigBuilder::new().build().unwrap();
+        let text = "// remind: 2024/06/27 remove\n// remind: 2024/06/28 drop\n";
+        let reminds = scan_text(&config, "./src/main.rs", text).unwrap().reminds;
+
+        let file: SnoozeFile = toml::from_str(&format!(
+            r#"
+[[snooze]]
+fingerprint = "{}"
+until = "2024-07-31"
+reason = "vendored"
+
+[[snooze]]
+file = "src/main.rs"
+line = 2
+until = "2024-07-01"
+
+[[snooze]]
+file = "src/gone.rs"
+line = 1
+until = "2024-07-01"
+"#,
+            reminds[0].fingerprint()
+        ))
+        .unwrap();
+        let timezone = Timezone::default();
+        // 2024-07-15T00:00:00Z
+        let now = 1721001600;
+
+        assert!(file.snoozed(&reminds[0], now, timezone).is_some());
+        // Matched, but the snooze has ended.
+        assert!(file.snoozed(&reminds[1], now, timezone).is_none());
+        assert_eq!(
+            file.stale(&reminds)
+                .iter()
+                .map(|s| s.target())
+                .collect::<Vec<_>>(),
+            vec!["src/gone.rs:1"]
+        );
+    }
+}
@@ -0,0 +1,469 @@
+use std::cell::OnceCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use anyhow::{anyhow, bail, Error};
+use chrono::Utc;
+use ignore::overrides::OverrideBuilder;
+use ignore::{WalkBuilder, WalkParallel, WalkState};
+
+use super::cache::Cache;
+use super::diagnostic::{Diagnostic, DiagnosticKind};
+use super::recurrence::resolve_recurrences;
+use super::relative::resolve_relative_deadlines;
+use super::scanner::{FileScan, Scanner};
+use super::trigger::TriggerContext;
+use super::Remind;
+use crate::config::builder::DEFAULT_CACHE_DIRECTORY;
+use crate::config::Config;
+use crate::git::blame::blame;
+use crate::git::diff::ChangedLines;
+use crate::git::Repo;
+
+// Enough to keep the walker threads busy without holding a whole large repository in memory.
+const CHANNEL_CAPACITY: usize = 1024;
+
+// Reminders are by far the most frequent event, so boxing them would only add allocations.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum ScanEvent {
+    /// A reminder that passed every filter of the config.
+    Remind(Remind),
+    /// A problem that did not stop the scan, such as an unreadable or binary file.
+    Diagnostic(Diagnostic),
+    /// A file was searched, or its reminders were taken from the cache.
+    FileVisited(String),
+    /// A file could not be walked or searched, or looks binary. Its `Diagnostic` comes first.
+    FileSkipped(String),
+}
+
+/// Stops a running scan. Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelHandle(Arc<AtomicBool>);
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Events of a scan running on background threads, in the order they are found.
+///
+/// Dropping the stream cancels the scan.
+pub struct ReminderStream {
+    receiver: Option<Receiver<ScanEvent>>,
+    cancel: CancelHandle,
+    handle: Option<JoinHandle<Result<(), Error>>>,
+}
+
+impl ReminderStream {
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone()
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Waits for the scan to stop and returns any error raised after it started,
+    /// such as a failure to write the cache.
+    pub fn finish(mut self) -> Result<(), Error> {
+        self.receiver.take();
+        match self.handle.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(Error::msg("reminder scan panicked")),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Iterator for ReminderStream {
+    type Item = ScanEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.as_ref()?.recv().ok()
+    }
+}
+
+impl Drop for ReminderStream {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+/// Starts scanning the configured directories and yields each reminder as soon as it is found.
+///
+/// Every filter of the config applies, except `sort_by_deadline`, which needs all reminders.
+pub fn stream_reminders(config: &Config) -> Result<ReminderStream, Error> {
+    let cache = config.cache().then(|| Cache::load(config));
+    start(config, cache, CancelHandle::default())
+}
+
+fn start(
+    config: &Config,
+    cache: Option<Cache>,
+    cancel: CancelHandle,
+) -> Result<ReminderStream, Error> {
+    let walker = build_walker(config)?;
+    let scanner = Scanner::new(config)?;
+    let changed = config.since().map(ChangedLines::since).transpose()?;
+    if config.blame() {
+        // Fail early when there is no repository to blame against.
+        Repo::discover()?;
+    }
+
+    let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+    let state = WalkContext {
+        config: config.clone(),
+        scanner,
+        changed,
+        cache,
+        triggers: TriggerContext::new(config),
+        now: Utc::now().timestamp(),
+        cancel: cancel.clone(),
+    };
+    let handle = thread::spawn(move || state.run(walker, sender));
+
+    Ok(ReminderStream {
+        receiver: Some(receiver),
+        cancel,
+        handle: Some(handle),
+    })
+}
+
+fn build_walker(config: &Config) -> Result<WalkParallel, Error> {
+    let directories = expand_directories(config.search_directories())?;
+    let mut directories = directories.iter();
+    let mut builder = WalkBuilder::new(directories.next().map_or(".", |d| d.as_str()));
+    for directory in directories {
+        builder.add(directory);
+    }
+
+    Ok(builder
+        .hidden(false)
+        .overrides(search_overrides(config)?.build()?)
+        .add_custom_ignore_filename(config.ignore_file_path())
+        .ignore(true)
+        .parents(false)
+        .threads(config.threads())
+        .build_parallel())
+}
+
+/// The `include` and `exclude` globs of `config`, plus the paths a scan never reads.
+pub(crate) fn search_overrides(config: &Config) -> Result<OverrideBuilder, Error> {
+    // Globs are resolved relative to the working directory, like `search_directories`.
+    let mut overrides = OverrideBuilder::new(".");
+    for glob in config.include() {
+        overrides.add(glob)?;
+    }
+    for glob in config.exclude() {
+        overrides.add(&format!("!{}", glob))?;
+    }
+    // The scan cache stores reminder messages, which must not be reported themselves.
+    overrides.add(&format!("!/{}/", DEFAULT_CACHE_DIRECTORY))?;
+    // Hidden files are searched, but git's object store is binary and never holds reminders.
+    overrides.add("!.git/")?;
+    Ok(overrides)
+}
+
+/// Resolves entries of `search_directories` such as `services/*/src` to the paths they match.
+/// Entries without glob characters are kept as they are, so a missing one is still reported
+/// while walking.
+pub(crate) fn expand_directories(directories: &[String]) -> Result<Vec<String>, Error> {
+    let mut expanded = Vec::new();
+    for directory in directories {
+        if !directory.contains(['*', '?', '[']) {
+            expanded.push(directory.clone());
+            continue;
+        }
+
+        let paths = glob::glob(directory)
+            .map_err(|e| anyhow!("Invalid search_directories entry `{}`: {}", directory, e))?;
+        let before = expanded.len();
+        for path in paths {
+            expanded.push(path?.display().to_string());
+        }
+        if expanded.len() == before {
+            bail!("search_directories entry `{}` matches nothing", directory);
+        }
+    }
+    Ok(expanded)
+}
+
+struct WalkContext {
+    config: Config,
+    scanner: Scanner,
+    changed: Option<ChangedLines>,
+    cache: Option<Cache>,
+    triggers: TriggerContext,
+    /// The clock recurring reminders are resolved against, fixed for the whole scan.
+    now: i64,
+    cancel: CancelHandle,
+}
+
+impl WalkContext {
+    fn run(self, walker: WalkParallel, sender: SyncSender<ScanEvent>) -> Result<(), Error> {
+        walker.run(|| {
+            // Each walker thread owns its searcher and repository handle, opened on first use.
+            let mut searcher = self.scanner.searcher();
+            let repo = OnceCell::new();
+            let sender = sender.clone();
+            let context = &self;
+
+            Box::new(move |e| {
+                if context.cancel.is_cancelled() {
+                    return WalkState::Quit;
+                }
+
+                let entry = match e {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        let diagnostic = walk_diagnostic(&e);
+                        let skipped = diagnostic.path.clone();
+                        let state = context.send(&sender, ScanEvent::Diagnostic(diagnostic));
+                        return match (state, skipped) {
+                            (WalkState::Continue, Some(path)) => {
+                                context.send(&sender, ScanEvent::FileSkipped(path))
+                            }
+                            (state, _) => state,
+                        };
+                    }
+                };
+                if entry.file_type().is_some_and(|t| t.is_dir()) {
+                    return WalkState::Continue;
+                }
+
+                let path = entry.path();
+                let mut scan = || context.scanner.scan_path(&mut searcher, path);
+                let mut scanned = match &context.cache {
+                    Some(cache) => cache.get_or_scan(path, scan),
+                    None => scan(),
+                };
+
+                if scanned
+                    .reminds
+                    .iter()
+                    .any(|r| r.relative_deadline.is_some())
+                {
+                    resolve_relative_deadlines(&mut scanned.reminds, open_repo(&repo));
+                }
+                context.triggers.evaluate(&mut scanned);
+                if scanned.reminds.iter().any(|r| r.recurrence.is_some()) {
+                    resolve_recurrences(
+                        &mut scanned.reminds,
+                        context.now,
+                        context.config.recurrence_window(),
+                    );
+                }
+                context.filter(&mut scanned);
+                if let (true, Some(repo)) = (context.config.blame(), open_repo(&repo)) {
+                    blame(repo, &mut scanned.reminds);
+                }
+
+                let path = path.display().to_string();
+                let progress = if scanned.diagnostics.iter().any(|d| d.kind.skips_file()) {
+                    ScanEvent::FileSkipped(path)
+                } else {
+                    ScanEvent::FileVisited(path)
+                };
+                let events = scanned
+                    .reminds
+                    .into_iter()
+                    .map(ScanEvent::Remind)
+                    .chain(scanned.diagnostics.into_iter().map(ScanEvent::Diagnostic))
+                    .chain([progress]);
+                for event in events {
+                    if let WalkState::Quit = context.send(&sender, event) {
+                        return WalkState::Quit;
+                    }
+                }
+                WalkState::Continue
+            })
+        });
+
+        if let Some(cache) = self.cache {
+            if !self.cancel.is_cancelled() {
+                cache.save()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn filter(&self, scan: &mut FileScan) {
+        if !self.config.remind_if_no_date() {
+            scan.reminds.retain(Remind::has_deadline);
+        }
+
+        if let Some(changed) = &self.changed {
+            scan.reminds.retain(|r| {
+                changed.contains(&r.position.file, r.position.line, r.position.end_line)
+            });
+            // Problems on unchanged lines are left to the run that introduced them.
+            scan.diagnostics.retain(|d| match (&d.path, d.line) {
+                (Some(path), Some(line)) => changed.contains(path, line, line),
+                _ => true,
+            });
+        }
+    }
+
+    fn send(&self, sender: &SyncSender<ScanEvent>, event: ScanEvent) -> WalkState {
+        // The receiver is gone once the stream is dropped or finished, which leaves the scan
+        // partial like a cancellation does.
+        match sender.send(event) {
+            Ok(()) => WalkState::Continue,
+            Err(_) => {
+                self.cancel.cancel();
+                WalkState::Quit
+            }
+        }
+    }
+}
+
+fn open_repo(repo: &OnceCell<Option<Repo>>) -> Option<&Repo> {
+    repo.get_or_init(|| Repo::discover().ok()).as_ref()
+}
+
+fn walk_diagnostic(error: &ignore::Error) -> Diagnostic {
+    match error {
+        ignore::Error::WithPath { path, err } => Diagnostic::new(
+            DiagnosticKind::WalkError,
+            Some(path.display().to_string()),
+            None,
+            err,
+        ),
+        ignore::Error::WithLineNumber { err, .. } | ignore::Error::WithDepth { err, .. } => {
+            walk_diagnostic(err)
+        }
+        _ => Diagnostic::new(DiagnosticKind::WalkError, None, None, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::builder::ConfigBuilder;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+
+    fn config_for(root: &std::path::Path) -> Config {
+        let config_path = root.with_extension("yml");
+        fs::write(
+            &config_path,
+            format!("search_directory: {}\n", root.display()),
+        )
+        .unwrap();
+        ConfigBuilder::new()
+            .config_file_path(Some(config_path.display().to_string()))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_stream_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("src");
+        fs::create_dir_all(&root).unwrap();
+        let text = root.join("main.rs");
+        let binary = root.join("image.png");
+        fs::write(&text, "// remind: 2024/06/27 a\n// remind: 2024/06/28 b\n").unwrap();
+        fs::write(&binary, "\0\0// remind: 2024/06/27 c\n").unwrap();
+
+        let mut stream = stream_reminders(&config_for(&root)).unwrap();
+        let mut reminds = vec![];
+        let mut visited = vec![];
+        let mut skipped = vec![];
+        let mut diagnostics = vec![];
+        for event in stream.by_ref() {
+            match event {
+                ScanEvent::Remind(remind) => reminds.push(remind.position.line),
+                ScanEvent::FileVisited(path) => visited.push(path),
+                ScanEvent::FileSkipped(path) => skipped.push(path),
+                ScanEvent::Diagnostic(diagnostic) => diagnostics.push(diagnostic.kind),
+            }
+        }
+        stream.finish().unwrap();
+
+        reminds.sort();
+        assert_eq!(reminds, vec![1, 2]);
+        assert_eq!(visited, vec![text.display().to_string()]);
+        assert_eq!(skipped, vec![binary.display().to_string()]);
+        assert_eq!(diagnostics, vec![DiagnosticKind::BinaryFile]);
+    }
+
+    #[test]
+    fn test_stream_cancel() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("src");
+        fs::create_dir_all(&root).unwrap();
+        for file in 0..64 {
+            fs::write(
+                root.join(format!("{}.rs", file)),
+                "// remind: 2024/06/27 a\n",
+            )
+            .unwrap();
+        }
+
+        let mut stream = stream_reminders(&config_for(&root)).unwrap();
+        let handle = stream.cancel_handle();
+        assert!(stream.next().is_some());
+        handle.cancel();
+        assert!(stream.cancel_handle().is_cancelled());
+
+        // Events already queued may still arrive, but the scan stops without an error.
+        stream.by_ref().for_each(drop);
+        stream.finish().unwrap();
+
+        // Dropping a stream part way cancels the scan instead of blocking the walker threads.
+        let mut stream = stream_reminders(&config_for(&root)).unwrap();
+        assert!(stream.next().is_some());
+        drop(stream);
+
+        // Only part of the tree was scanned, so the cache is not saved.
+        let cache_path = dir.path().join("cache");
+        let cancel = CancelHandle::default();
+        cancel.cancel();
+        let cache = Cache::open(cache_path.clone(), "key".into());
+        let stream = start(&config_for(&root), Some(cache), cancel).unwrap();
+        stream.finish().unwrap();
+        assert!(!cache_path.exists());
+
+        let cache = Cache::open(cache_path.clone(), "key".into());
+        let mut stream = start(&config_for(&root), Some(cache), CancelHandle::default()).unwrap();
+        assert_eq!(stream.by_ref().count(), 128);
+        stream.finish().unwrap();
+        assert!(cache_path.exists());
+    }
+
+    #[test]
+    fn test_expand_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for directory in ["services/a/src", "services/b/src", "services/c"] {
+            fs::create_dir_all(root.join(directory)).unwrap();
+        }
+        let root = root.display().to_string();
+
+        let expanded =
+            expand_directories(&[format!("{}/services/*/src", root), format!("{}/libs", root)])
+                .unwrap();
+        assert_eq!(
+            expanded,
+            vec![
+                format!("{}/services/a/src", root),
+                format!("{}/services/b/src", root),
+                format!("{}/libs", root),
+            ]
+        );
+
+        let error = expand_directories(&[format!("{}/apps/*", root)]).unwrap_err();
+        assert!(error.to_string().contains("matches nothing"));
+        assert!(expand_directories(&["services/[".to_string()]).is_err());
+    }
+}
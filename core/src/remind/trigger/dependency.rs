@@ -0,0 +1,134 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::lockfile::LockedPackages;
+use super::version::{is_reached, parse_version};
+
+/// `when serde >= 2.0`, `when @babel/core>7` or `when tokio removed`.
+static DEPENDENCY_TRIGGER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\bwhen\s+([@\w][\w.@/\-]*)\s*(?:(>=|<=|>|<|=)\s*(\d+(?:\.\d+){0,2}(?:-[0-9A-Za-z.\-]+)?)|(removed)\b)",
+    )
+    .unwrap()
+});
+
+/// What a dependency trigger waits for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyCondition {
+    /// Due once a locked version satisfies the requirement, e.g. `>=2.0`.
+    Version(String),
+    /// Due once no lockfile locks the package anymore.
+    Removed,
+}
+
+/// Finds a dependency trigger in `text`, returning the package name and its condition.
+pub(crate) fn dependency_condition(text: &str) -> Option<(String, DependencyCondition)> {
+    let captures = DEPENDENCY_TRIGGER.captures(text)?;
+    let condition = match (captures.get(2), captures.get(3)) {
+        (Some(operator), Some(version)) => {
+            parse_version(version.as_str()).ok()?;
+            DependencyCondition::Version(format!("{}{}", operator.as_str(), version.as_str()))
+        }
+        _ => DependencyCondition::Removed,
+    };
+    Some((captures[1].to_string(), condition))
+}
+
+/// Decides `condition` for `name`, returning the highest locked version and whether it is due.
+///
+/// When several versions are locked, a version requirement is due as soon as one satisfies it.
+/// Lockfiles cannot tell a removed package from one that was never there, but a name that only
+/// differs from a locked one in case or separators is reported, as it is most likely misspelt.
+pub(crate) fn evaluate(
+    name: &str,
+    condition: &DependencyCondition,
+    locked: &LockedPackages,
+) -> Result<(Option<String>, bool), String> {
+    let versions = locked.versions(name).unwrap_or_default();
+    if versions.is_empty() {
+        if let Some(similar) = locked.similar(name) {
+            return Err(format!(
+                "no lockfile locks `{}`, but `{}` is locked; check the name",
+                name, similar
+            ));
+        }
+    }
+
+    let current = versions
+        .iter()
+        .max_by_key(|version| parse_version(version).ok())
+        .cloned();
+
+    let due = match condition {
+        DependencyCondition::Version(requirement) => versions.iter().any(|version| {
+            parse_version(version).is_ok_and(|version| is_reached(requirement, &version))
+        }),
+        DependencyCondition::Removed => versions.is_empty(),
+    };
+    Ok((current, due))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_dependency_condition() {
+        assert_eq!(
+            dependency_condition("// remind: when serde >= 2.0 drop the workaround"),
+            Some((
+                "serde".to_string(),
+                DependencyCondition::Version(">=2.0".to_string())
+            ))
+        );
+        assert_eq!(
+            dependency_condition("// remind: when @babel/core>7.25"),
+            Some((
+                "@babel/core".to_string(),
+                DependencyCondition::Version(">7.25".to_string())
+            ))
+        );
+        assert_eq!(
+            dependency_condition("// remind: when tokio removed, drop the runtime shim"),
+            Some(("tokio".to_string(), DependencyCondition::Removed))
+        );
+        assert_eq!(dependency_condition("// remind: when possible"), None);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut locked = LockedPackages::default();
+        locked.extend([
+            ("serde".to_string(), "1.0.219".to_string()),
+            ("serde".to_string(), "2.0.0".to_string()),
+            ("typing_extensions".to_string(), "4.12.2".to_string()),
+        ]);
+        let version = |requirement: &str| DependencyCondition::Version(requirement.to_string());
+
+        assert_eq!(
+            evaluate("serde", &version(">=2.0"), &locked),
+            Ok((Some("2.0.0".to_string()), true))
+        );
+        assert_eq!(
+            evaluate("serde", &DependencyCondition::Removed, &locked),
+            Ok((Some("2.0.0".to_string()), false))
+        );
+        // A package that is gone cannot be told apart from one that was never locked.
+        assert_eq!(
+            evaluate("tokio", &DependencyCondition::Removed, &locked),
+            Ok((None, true))
+        );
+        assert_eq!(
+            evaluate("tokio", &version(">=1.0"), &locked),
+            Ok((None, false))
+        );
+        assert!(evaluate("Serde", &DependencyCondition::Removed, &locked)
+            .unwrap_err()
+            .contains("`serde` is locked"));
+        assert!(evaluate("typing-extensions", &version(">=4"), &locked).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// `when #1234 closed` or `when JIRA-42 resolved`.
+static ISSUE_TRIGGER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bwhen\s+(#\d+|[A-Z][A-Z0-9_]*-\d+)\s+(?:closed|resolved)\b").unwrap()
+});
+
+/// The state of an issue as reported by a tracker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueState {
+    /// `open` on GitHub, `opened` on GitLab.
+    #[serde(alias = "opened")]
+    Open,
+    #[serde(alias = "resolved", alias = "done")]
+    Closed,
+}
+
+/// Finds an issue trigger in `text`, returning the issue id as written, e.g. `#1234`.
+pub(crate) fn issue_id(text: &str) -> Option<String> {
+    ISSUE_TRIGGER
+        .captures(text)
+        .map(|captures| captures[1].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_issue_id() {
+        let id = issue_id("// remind: when #1234 closed drop the workaround");
+        assert_eq!(id.as_deref(), Some("#1234"));
+
+        let id = issue_id("// remind: when JIRA-42 resolved");
+        assert_eq!(id.as_deref(), Some("JIRA-42"));
+
+        assert_eq!(issue_id("// remind: when #1234 is closed"), None);
+        assert_eq!(issue_id("// remind: see #1234"), None);
+    }
+}
@@ -0,0 +1,482 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Error};
+use ignore::WalkBuilder;
+use serde_json::Value;
+
+use super::version::parse_version;
+use crate::config::Config;
+use crate::remind::stream::{expand_directories, search_overrides};
+
+/// Reads the packages locked by one kind of lockfile.
+///
+/// Parsers besides the built-in ones are registered with
+/// [`ConfigBuilder::lockfile_parser`](crate::config::builder::ConfigBuilder::lockfile_parser).
+pub trait LockfileParser: Send + Sync {
+    /// The name of the lockfile, e.g. `Cargo.lock`.
+    fn file_name(&self) -> &str;
+
+    /// The name of a file that is read instead when it sits next to this one, e.g. `go.mod` for
+    /// `go.sum`.
+    fn superseded_by(&self) -> Option<&str> {
+        None
+    }
+
+    /// The name and version of every locked package.
+    fn parse(&self, content: &str) -> Result<Vec<(String, String)>, Error>;
+}
+
+static PARSERS: [&dyn LockfileParser; 5] = [&CargoLock, &PackageLock, &GoMod, &GoSum, &PoetryLock];
+
+/// The lockfile parsers registered besides the built-in ones.
+#[derive(Clone, Default)]
+pub(crate) struct LockfileParsers(Vec<Arc<dyn LockfileParser>>);
+
+impl LockfileParsers {
+    pub(crate) fn push(&mut self, parser: Arc<dyn LockfileParser>) {
+        self.0.push(parser);
+    }
+
+    /// The parser for `file_name`, preferring registered parsers over the built-in ones.
+    fn find(&self, file_name: &str) -> Option<&dyn LockfileParser> {
+        let registered = self.0.iter().map(|parser| parser.as_ref());
+        registered
+            .chain(PARSERS.iter().copied())
+            .find(|parser| parser.file_name() == file_name)
+    }
+}
+
+impl fmt::Debug for LockfileParsers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|parser| parser.file_name()))
+            .finish()
+    }
+}
+
+struct CargoLock;
+
+impl LockfileParser for CargoLock {
+    fn file_name(&self) -> &str {
+        "Cargo.lock"
+    }
+
+    fn parse(&self, content: &str) -> Result<Vec<(String, String)>, Error> {
+        toml_packages(content)
+    }
+}
+
+/// npm's `package-lock.json`, both the `packages` map of lockfile v2/v3 and the nested
+/// `dependencies` of v1.
+struct PackageLock;
+
+impl LockfileParser for PackageLock {
+    fn file_name(&self) -> &str {
+        "package-lock.json"
+    }
+
+    fn parse(&self, content: &str) -> Result<Vec<(String, String)>, Error> {
+        let lock: Value = serde_json::from_str(content)?;
+        let mut packages = vec![];
+
+        if let Some(entries) = lock["packages"].as_object() {
+            for (path, package) in entries {
+                // The empty path is the project itself.
+                let Some((_, name)) = path.rsplit_once("node_modules/") else {
+                    continue;
+                };
+                if let Some(version) = package["version"].as_str() {
+                    packages.push((name.to_string(), version.to_string()));
+                }
+            }
+        } else {
+            npm_v1_dependencies(&lock["dependencies"], &mut packages);
+        }
+
+        Ok(packages)
+    }
+}
+
+fn npm_v1_dependencies(dependencies: &Value, packages: &mut Vec<(String, String)>) {
+    let Some(dependencies) = dependencies.as_object() else {
+        return;
+    };
+    for (name, dependency) in dependencies {
+        if let Some(version) = dependency["version"].as_str() {
+            packages.push((name.to_string(), version.to_string()));
+        }
+        npm_v1_dependencies(&dependency["dependencies"], packages);
+    }
+}
+
+/// Go's `go.mod`, whose `require` directives list the version the build selects for every
+/// module, indirect ones included since Go 1.17.
+struct GoMod;
+
+impl LockfileParser for GoMod {
+    fn file_name(&self) -> &str {
+        "go.mod"
+    }
+
+    fn parse(&self, content: &str) -> Result<Vec<(String, String)>, Error> {
+        let mut packages = vec![];
+        let mut in_require = false;
+
+        for line in content.lines() {
+            let line = line.split("//").next().unwrap_or_default().trim();
+            let requirement = match line.strip_prefix("require") {
+                _ if in_require && line == ")" => {
+                    in_require = false;
+                    continue;
+                }
+                _ if in_require => line,
+                Some(rest) if rest.trim() == "(" => {
+                    in_require = true;
+                    continue;
+                }
+                Some(rest) if rest.starts_with(char::is_whitespace) => rest,
+                _ => continue,
+            };
+
+            let mut fields = requirement.split_whitespace();
+            if let (Some(module), Some(version)) = (fields.next(), fields.next()) {
+                packages.push((module.to_string(), version.to_string()));
+            }
+        }
+
+        Ok(packages)
+    }
+}
+
+/// Go's `go.sum`, read only for modules without a `go.mod` next to it. It also keeps versions
+/// that lost minimal version selection, so only the highest version of each module is taken.
+struct GoSum;
+
+impl LockfileParser for GoSum {
+    fn file_name(&self) -> &str {
+        "go.sum"
+    }
+
+    fn superseded_by(&self) -> Option<&str> {
+        Some("go.mod")
+    }
+
+    fn parse(&self, content: &str) -> Result<Vec<(String, String)>, Error> {
+        let mut highest: BTreeMap<&str, &str> = BTreeMap::new();
+        for line in content.lines() {
+            let mut fields = line.split_whitespace();
+            let (Some(module), Some(version)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            // Every module appears once for its content and once for its `go.mod`.
+            let version = version.strip_suffix("/go.mod").unwrap_or(version);
+            highest
+                .entry(module)
+                .and_modify(|highest| {
+                    if parse_version(version).ok() > parse_version(highest).ok() {
+                        *highest = version;
+                    }
+                })
+                .or_insert(version);
+        }
+
+        Ok(highest
+            .into_iter()
+            .map(|(module, version)| (module.to_string(), version.to_string()))
+            .collect())
+    }
+}
+
+struct PoetryLock;
+
+impl LockfileParser for PoetryLock {
+    fn file_name(&self) -> &str {
+        "poetry.lock"
+    }
+
+    fn parse(&self, content: &str) -> Result<Vec<(String, String)>, Error> {
+        toml_packages(content)
+    }
+}
+
+/// The `[[package]]` tables shared by `Cargo.lock` and `poetry.lock`.
+fn toml_packages(content: &str) -> Result<Vec<(String, String)>, Error> {
+    let lock: toml::Table = toml::from_str(content)?;
+    let packages = lock
+        .get("package")
+        .and_then(|packages| packages.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|package| {
+            let name = package.get("name")?.as_str()?;
+            let version = package.get("version")?.as_str()?;
+            Some((name.to_string(), version.to_string()))
+        })
+        .collect();
+    Ok(packages)
+}
+
+/// The versions of every package locked anywhere in the scanned tree.
+#[derive(Debug, Default)]
+pub(crate) struct LockedPackages(BTreeMap<String, Vec<String>>);
+
+impl LockedPackages {
+    /// Reads every supported lockfile under the search directories, skipping files the scan
+    /// skips, i.e. those not matching `include` and excluded or ignored ones.
+    pub(crate) fn find(config: &Config) -> Result<Self, Error> {
+        let directories = expand_directories(config.search_directories())?;
+        let mut directories = directories.iter();
+        let mut builder = WalkBuilder::new(directories.next().map_or(".", |d| d.as_str()));
+        for directory in directories {
+            builder.add(directory);
+        }
+
+        let mut overrides = search_overrides(config)?;
+        // Installed packages carry lockfiles of their own, which say nothing about the project.
+        overrides.add("!node_modules/")?;
+
+        let walker = builder
+            .hidden(false)
+            .overrides(overrides.build()?)
+            .add_custom_ignore_filename(config.ignore_file_path())
+            .parents(false)
+            .build();
+
+        let mut locked = LockedPackages::default();
+        let mut found = false;
+        for entry in walker {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy();
+            let Some(parser) = config.lockfile_parsers().find(&file_name) else {
+                continue;
+            };
+
+            let path = entry.path();
+            if let Some(superseding) = parser.superseded_by() {
+                if path.with_file_name(superseding).is_file() {
+                    continue;
+                }
+            }
+            let packages = fs::read_to_string(path)
+                .map_err(Error::from)
+                .and_then(|content| parser.parse(&content))
+                .map_err(|e| anyhow!("failed to read `{}`: {}", path.display(), e))?;
+            locked.extend(packages);
+            found = true;
+        }
+
+        match found {
+            true => Ok(locked),
+            false => Err(anyhow!("no lockfile found in the search directories")),
+        }
+    }
+
+    /// The locked versions of `name`, or `None` if no lockfile locks it.
+    pub(crate) fn versions(&self, name: &str) -> Option<&[String]> {
+        self.0.get(name).map(Vec::as_slice)
+    }
+
+    /// A locked package whose name differs from `name` only in case or in `-`, `_` and `.`,
+    /// which is most likely what a reminder naming `name` means.
+    pub(crate) fn similar(&self, name: &str) -> Option<&str> {
+        let normalize = |name: &str| name.to_lowercase().replace(['_', '.'], "-");
+        let normalized = normalize(name);
+        self.0
+            .keys()
+            .find(|locked| normalize(locked) == normalized)
+            .map(String::as_str)
+    }
+}
+
+impl Extend<(String, String)> for LockedPackages {
+    fn extend<T: IntoIterator<Item = (String, String)>>(&mut self, packages: T) {
+        for (name, version) in packages {
+            self.0.entry(name).or_default().push(version);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn pair(name: &str, version: &str) -> (String, String) {
+        (name.to_string(), version.to_string())
+    }
+
+    #[test]
+    fn test_cargo_lock() {
+        let lock = r#"
+version = 4
+
+[[package]]
+name = "serde"
+version = "1.0.219"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "app"
+version = "0.1.0"
+"#;
+        assert_eq!(
+            CargoLock.parse(lock).unwrap(),
+            vec![pair("serde", "1.0.219"), pair("app", "0.1.0")]
+        );
+    }
+
+    #[test]
+    fn test_package_lock() {
+        let v3 = r#"{
+            "lockfileVersion": 3,
+            "packages": {
+                "": {"name": "app", "version": "1.0.0"},
+                "node_modules/react": {"version": "18.3.1"},
+                "node_modules/@babel/core": {"version": "7.24.0"},
+                "node_modules/a/node_modules/b": {"version": "2.0.0"}
+            }
+        }"#;
+        let mut packages = PackageLock.parse(v3).unwrap();
+        packages.sort();
+        assert_eq!(
+            packages,
+            vec![
+                pair("@babel/core", "7.24.0"),
+                pair("b", "2.0.0"),
+                pair("react", "18.3.1"),
+            ]
+        );
+
+        let v1 = r#"{
+            "lockfileVersion": 1,
+            "dependencies": {
+                "a": {"version": "1.0.0", "dependencies": {"b": {"version": "2.0.0"}}}
+            }
+        }"#;
+        assert_eq!(
+            PackageLock.parse(v1).unwrap(),
+            vec![pair("a", "1.0.0"), pair("b", "2.0.0")]
+        );
+    }
+
+    #[test]
+    fn test_go_mod() {
+        let go_mod = r#"module example.com/app
+
+go 1.22
+
+require github.com/google/uuid v1.6.0
+
+require (
+	golang.org/x/text v0.14.0
+	golang.org/x/sys v0.20.0 // indirect
+)
+
+replace golang.org/x/net => golang.org/x/net v0.1.0
+"#;
+        assert_eq!(
+            GoMod.parse(go_mod).unwrap(),
+            vec![
+                pair("github.com/google/uuid", "v1.6.0"),
+                pair("golang.org/x/text", "v0.14.0"),
+                pair("golang.org/x/sys", "v0.20.0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_go_sum() {
+        let sum = "golang.org/x/text v0.3.0 h1:abc=
+golang.org/x/text v0.3.0/go.mod h1:def=
+golang.org/x/text v0.14.0 h1:ghi=
+golang.org/x/text v0.14.0/go.mod h1:jkl=
+github.com/google/uuid v1.6.0/go.mod h1:mno=
+";
+        assert_eq!(
+            GoSum.parse(sum).unwrap(),
+            vec![
+                pair("github.com/google/uuid", "v1.6.0"),
+                pair("golang.org/x/text", "v0.14.0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_poetry_lock() {
+        let lock = "[[package]]\nname = \"requests\"\nversion = \"2.31.0\"\noptional = false\n";
+        assert_eq!(
+            PoetryLock.parse(lock).unwrap(),
+            vec![pair("requests", "2.31.0")]
+        );
+    }
+
+    struct Requirements;
+
+    impl LockfileParser for Requirements {
+        fn file_name(&self) -> &str {
+            "requirements.txt"
+        }
+
+        fn parse(&self, content: &str) -> Result<Vec<(String, String)>, Error> {
+            Ok(content
+                .lines()
+                .filter_map(|line| line.split_once("=="))
+                .map(|(name, version)| pair(name.trim(), version.trim()))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (path, content) in [
+            ("go/go.sum", "golang.org/x/text v0.14.0 h1:abc=\n"),
+            (
+                "mod/go.mod",
+                "module app\n\nrequire golang.org/x/sys v0.20.0\n",
+            ),
+            ("mod/go.sum", "golang.org/x/sys v0.1.0 h1:abc=\n"),
+            ("py/requirements.txt", "requests==2.31.0\n"),
+            ("other/requirements.txt", "flask==3.0.0\n"),
+        ] {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let config_path = root.join("remind.yml");
+        let root = root.to_string_lossy();
+        fs::write(
+            &config_path,
+            format!(
+                "search_directory: {root}\ninclude:\n  - \"**/go/**\"\n  - \"**/mod/**\"\n  - \"**/py/**\"\n"
+            ),
+        )
+        .unwrap();
+        let config = crate::config::builder::ConfigBuilder::new()
+            .config_file_path(Some(config_path.to_string_lossy().into_owned()))
+            .lockfile_parser(Requirements)
+            .build()
+            .unwrap();
+
+        let locked = LockedPackages::find(&config).unwrap();
+        assert_eq!(
+            locked.versions("golang.org/x/text"),
+            Some(&["v0.14.0".to_string()][..])
+        );
+        // `go.sum` is only read without a `go.mod` next to it.
+        assert_eq!(
+            locked.versions("golang.org/x/sys"),
+            Some(&["v0.20.0".to_string()][..])
+        );
+        assert_eq!(
+            locked.versions("requests"),
+            Some(&["2.31.0".to_string()][..])
+        );
+        assert_eq!(locked.versions("flask"), None);
+    }
+}
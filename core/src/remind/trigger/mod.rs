@@ -0,0 +1,185 @@
+use std::sync::OnceLock;
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use super::diagnostic::{Diagnostic, DiagnosticKind};
+use super::scanner::FileScan;
+use crate::config::Config;
+use dependency::dependency_condition;
+use issue::issue_id;
+use lockfile::LockedPackages;
+pub(crate) use lockfile::LockfileParsers;
+use tracker::{tracker_provider, TrackerProvider};
+use version::{current_version, is_reached, version_requirement};
+
+mod dependency;
+mod issue;
+mod lockfile;
+mod tracker;
+mod version;
+
+pub use dependency::DependencyCondition;
+pub use issue::IssueState;
+pub use lockfile::LockfileParser;
+
+/// A condition besides the date that makes a reminder due.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Trigger {
+    /// Due once the version of the project satisfies `requirement`, e.g. `>=3.0.0`.
+    Version {
+        requirement: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        current: Option<String>,
+        #[serde(default)]
+        due: bool,
+    },
+    /// Due once a package locked in the scanned tree meets `condition`, e.g. `when serde >= 2.0`.
+    Dependency {
+        name: String,
+        condition: DependencyCondition,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        current: Option<String>,
+        #[serde(default)]
+        due: bool,
+    },
+    /// Due once the issue `id` is closed, e.g. `when #1234 closed` or `when JIRA-42 resolved`.
+    Issue {
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state: Option<IssueState>,
+        #[serde(default)]
+        due: bool,
+    },
+}
+
+impl Trigger {
+    pub fn is_due(&self) -> bool {
+        match self {
+            Trigger::Version { due, .. }
+            | Trigger::Dependency { due, .. }
+            | Trigger::Issue { due, .. } => *due,
+        }
+    }
+}
+
+/// Finds a trigger in the text of a reminder.
+pub(crate) fn parse_trigger(text: &str) -> Option<Trigger> {
+    if let Some(requirement) = version_requirement(text) {
+        return Some(Trigger::Version {
+            requirement,
+            current: None,
+            due: false,
+        });
+    }
+
+    if let Some(id) = issue_id(text) {
+        return Some(Trigger::Issue {
+            id,
+            state: None,
+            due: false,
+        });
+    }
+
+    dependency_condition(text).map(|(name, condition)| Trigger::Dependency {
+        name,
+        condition,
+        current: None,
+        due: false,
+    })
+}
+
+/// Evaluates triggers against the state of the project, which is read at most once per run.
+pub(crate) struct TriggerContext {
+    config: Config,
+    version: OnceLock<Result<Version, String>>,
+    packages: OnceLock<Result<LockedPackages, String>>,
+    tracker: OnceLock<Result<Box<dyn TrackerProvider>, String>>,
+}
+
+impl TriggerContext {
+    pub(crate) fn new(config: &Config) -> Self {
+        Self {
+            config: config.clone(),
+            version: OnceLock::new(),
+            packages: OnceLock::new(),
+            tracker: OnceLock::new(),
+        }
+    }
+
+    /// Decides whether each trigger in `scan` is due, reporting triggers that cannot be decided.
+    pub(crate) fn evaluate(&self, scan: &mut FileScan) {
+        let FileScan {
+            reminds,
+            diagnostics,
+        } = scan;
+
+        for remind in reminds.iter_mut() {
+            let Some(trigger) = &mut remind.trigger else {
+                continue;
+            };
+
+            let evaluated = match trigger {
+                Trigger::Version {
+                    requirement,
+                    current,
+                    due,
+                } => self.version().map(|version| {
+                    *current = Some(version.to_string());
+                    *due = is_reached(requirement, version);
+                }),
+                Trigger::Issue { id, state, due } => self.issue_state(id).map(|issue_state| {
+                    *state = Some(issue_state);
+                    *due = issue_state == IssueState::Closed;
+                }),
+                Trigger::Dependency {
+                    name,
+                    condition,
+                    current,
+                    due,
+                } => self
+                    .packages()
+                    .and_then(|packages| dependency::evaluate(name, condition, packages))
+                    .map(|evaluated| (*current, *due) = evaluated),
+            };
+
+            if let Err(message) = evaluated {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::TriggerError,
+                    Some(remind.position.file.clone()),
+                    Some(remind.position.line),
+                    message,
+                ));
+            }
+        }
+    }
+
+    fn version(&self) -> Result<&Version, String> {
+        self.version
+            .get_or_init(|| {
+                current_version(self.config.version_source()).map_err(|e| e.to_string())
+            })
+            .as_ref()
+            .map_err(String::clone)
+    }
+
+    fn packages(&self) -> Result<&LockedPackages, String> {
+        self.packages
+            .get_or_init(|| LockedPackages::find(&self.config).map_err(|e| e.to_string()))
+            .as_ref()
+            .map_err(String::clone)
+    }
+
+    fn issue_state(&self, id: &str) -> Result<IssueState, String> {
+        let tracker = self
+            .tracker
+            .get_or_init(|| match self.config.issue_tracker() {
+                Some(tracker) => tracker_provider(tracker).map_err(|e| e.to_string()),
+                None => Err("no `issue_tracker` is configured".to_string()),
+            })
+            .as_ref()
+            .map_err(String::clone)?;
+        tracker.issue_state(id).map_err(|e| e.to_string())
+    }
+}
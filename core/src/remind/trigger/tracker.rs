@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, Error};
+use serde_json::Value;
+use ureq::Agent;
+
+use super::issue::IssueState;
+use crate::config::builder::{IssueTracker, TrackerKind};
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Looks up the state of the issues referenced by triggers.
+pub(crate) trait TrackerProvider: Send + Sync {
+    /// The state of the issue `id`, as written in the comment, e.g. `#1234` or `JIRA-42`.
+    fn issue_state(&self, id: &str) -> Result<IssueState, Error>;
+}
+
+/// Builds the provider configured by `tracker`, caching every lookup for the rest of the run.
+pub(crate) fn tracker_provider(tracker: &IssueTracker) -> Result<Box<dyn TrackerProvider>, Error> {
+    let provider: Box<dyn TrackerProvider> = match tracker.provider {
+        TrackerKind::Github | TrackerKind::Gitlab => Box::new(HttpTracker::new(tracker)),
+        TrackerKind::Snapshot => Box::new(SnapshotTracker::load(&tracker.snapshot)?),
+    };
+    Ok(Box::new(CachedTracker {
+        provider,
+        states: Mutex::new(HashMap::new()),
+    }))
+}
+
+/// The issues API of GitHub or GitLab, which both number issues per repository as `#1234`.
+struct HttpTracker {
+    kind: TrackerKind,
+    repository: String,
+    issues_url: String,
+    token: Option<String>,
+    agent: Agent,
+}
+
+impl HttpTracker {
+    fn new(tracker: &IssueTracker) -> Self {
+        let (default_api_url, default_token_env) = match tracker.provider {
+            TrackerKind::Gitlab => ("https://gitlab.com/api/v4", "GITLAB_TOKEN"),
+            _ => ("https://api.github.com", "GITHUB_TOKEN"),
+        };
+        let api_url = match tracker.api_url.as_str() {
+            "" => default_api_url,
+            api_url => api_url.trim_end_matches('/'),
+        };
+        let issues_url = match tracker.provider {
+            TrackerKind::Gitlab => format!(
+                "{}/projects/{}/issues",
+                api_url,
+                tracker.repository.replace('/', "%2F")
+            ),
+            _ => format!("{}/repos/{}/issues", api_url, tracker.repository),
+        };
+        let token_env = match tracker.token_env.as_str() {
+            "" => default_token_env,
+            token_env => token_env,
+        };
+        let agent = Agent::config_builder()
+            .timeout_global(Some(HTTP_TIMEOUT))
+            .build()
+            .into();
+
+        Self {
+            kind: tracker.provider,
+            repository: tracker.repository.clone(),
+            issues_url,
+            token: std::env::var(token_env).ok().filter(|t| !t.is_empty()),
+            agent,
+        }
+    }
+}
+
+impl TrackerProvider for HttpTracker {
+    fn issue_state(&self, id: &str) -> Result<IssueState, Error> {
+        let number = id
+            .strip_prefix('#')
+            .and_then(|number| number.parse::<u64>().ok())
+            .ok_or_else(|| {
+                let provider = match self.kind {
+                    TrackerKind::Gitlab => "gitlab",
+                    _ => "github",
+                };
+                anyhow!(
+                    "`{}` is not an issue number like `#1234`, which is all {} resolves; \
+                     use the `snapshot` provider for other trackers",
+                    id,
+                    provider
+                )
+            })?;
+
+        let mut request = self
+            .agent
+            .get(format!("{}/{}", self.issues_url, number))
+            .header("Accept", "application/json")
+            .header("User-Agent", "reminder-lint");
+        if let Some(token) = &self.token {
+            request = match self.kind {
+                TrackerKind::Gitlab => request.header("PRIVATE-TOKEN", token),
+                _ => request.header("Authorization", format!("Bearer {}", token)),
+            };
+        }
+
+        let issue: Value = request
+            .call()
+            .and_then(|mut response| response.body_mut().read_json())
+            .map_err(|e| anyhow!("failed to fetch {} of {}: {}", id, self.repository, e))?;
+        Ok(serde_json::from_value(issue["state"].clone())?)
+    }
+}
+
+/// Issue states read from a JSON object such as `{"#1234": "closed", "JIRA-42": "open"}`,
+/// so that CI and tests can run without network access.
+struct SnapshotTracker {
+    path: String,
+    states: HashMap<String, IssueState>,
+}
+
+impl SnapshotTracker {
+    fn load(path: &str) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("failed to read the issue snapshot `{}`: {}", path, e))?;
+        Ok(Self {
+            path: path.to_string(),
+            states: parse_snapshot(&content)
+                .map_err(|e| anyhow!("invalid issue snapshot `{}`: {}", path, e))?,
+        })
+    }
+}
+
+fn parse_snapshot(content: &str) -> Result<HashMap<String, IssueState>, Error> {
+    Ok(serde_json::from_str(content)?)
+}
+
+impl TrackerProvider for SnapshotTracker {
+    fn issue_state(&self, id: &str) -> Result<IssueState, Error> {
+        self.states
+            .get(id)
+            .copied()
+            .ok_or_else(|| anyhow!("`{}` is not in the issue snapshot `{}`", id, self.path))
+    }
+}
+
+/// The answer for one issue, filled in by the first thread that asks for it.
+type StateSlot = Arc<OnceLock<Result<IssueState, String>>>;
+
+/// Remembers every answer of `provider`, so each issue is looked up at most once per run.
+struct CachedTracker {
+    provider: Box<dyn TrackerProvider>,
+    states: Mutex<HashMap<String, StateSlot>>,
+}
+
+impl TrackerProvider for CachedTracker {
+    fn issue_state(&self, id: &str) -> Result<IssueState, Error> {
+        // The map is only locked to find the slot of `id`. Threads asking for the same issue
+        // wait on that slot for the first answer, while other issues are looked up in parallel.
+        let slot = self
+            .states
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(id.to_string())
+            .or_default()
+            .clone();
+        slot.get_or_init(|| self.provider.issue_state(id).map_err(|e| e.to_string()))
+            .clone()
+            .map_err(Error::msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_snapshot() {
+        let states =
+            parse_snapshot(r##"{"#1234": "closed", "#7": "opened", "JIRA-42": "resolved"}"##)
+                .unwrap();
+        assert_eq!(states["#1234"], IssueState::Closed);
+        assert_eq!(states["#7"], IssueState::Open);
+        assert_eq!(states["JIRA-42"], IssueState::Closed);
+
+        assert!(parse_snapshot(r##"{"#1": "merged"}"##).is_err());
+    }
+
+    #[test]
+    fn test_cached_tracker() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Counting(Arc<AtomicUsize>);
+        impl TrackerProvider for Counting {
+            fn issue_state(&self, _id: &str) -> Result<IssueState, Error> {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                Ok(IssueState::Open)
+            }
+        }
+
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let cached = CachedTracker {
+            provider: Box::new(Counting(lookups.clone())),
+            states: Mutex::new(HashMap::new()),
+        };
+        for _ in 0..3 {
+            assert_eq!(cached.issue_state("#1").unwrap(), IssueState::Open);
+        }
+        cached.issue_state("#2").unwrap();
+
+        assert_eq!(lookups.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_cached_tracker_looks_up_issues_in_parallel() {
+        use std::sync::Barrier;
+
+        // Every lookup waits for the other one, so this only finishes when they overlap.
+        struct Waiting(Barrier);
+        impl TrackerProvider for Waiting {
+            fn issue_state(&self, _id: &str) -> Result<IssueState, Error> {
+                self.0.wait();
+                Ok(IssueState::Closed)
+            }
+        }
+
+        let cached = CachedTracker {
+            provider: Box::new(Waiting(Barrier::new(2))),
+            states: Mutex::new(HashMap::new()),
+        };
+        std::thread::scope(|scope| {
+            let first = scope.spawn(|| cached.issue_state("#1").unwrap());
+            let second = scope.spawn(|| cached.issue_state("#2").unwrap());
+            assert_eq!(first.join().unwrap(), IssueState::Closed);
+            assert_eq!(second.join().unwrap(), IssueState::Closed);
+        });
+    }
+
+    #[test]
+    fn test_http_tracker_rejects_other_ids() {
+        let tracker = HttpTracker::new(&IssueTracker {
+            provider: TrackerKind::Github,
+            repository: "CyberAgent/reminder-lint".into(),
+            api_url: String::new(),
+            token_env: String::new(),
+            snapshot: String::new(),
+        });
+        let error = tracker.issue_state("JIRA-42").unwrap_err().to_string();
+        assert!(error.contains("use the `snapshot` provider"));
+    }
+}
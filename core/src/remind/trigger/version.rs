@@ -0,0 +1,238 @@
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use anyhow::{anyhow, Error};
+use regex::Regex;
+use std::cmp::Ordering;
+
+use semver::Version;
+
+use crate::git::Repo;
+
+/// `version>=3.0.0`, `version > 2.1` or `version=1.0.0-rc.1`.
+static VERSION_TRIGGER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\bversion\s*(>=|<=|>|<|=)\s*(\d+(?:\.\d+){0,2}(?:-[0-9A-Za-z.\-]+)?)").unwrap()
+});
+
+/// The value of `version_source` that reads the nearest tag reachable from `HEAD`.
+pub(crate) const GIT_TAG_SOURCE: &str = "git_tag";
+const AUTO_DETECTED_FILES: [&str; 3] = ["Cargo.toml", "package.json", "VERSION"];
+
+/// Finds a version requirement in `text`, normalized to semver syntax, e.g. `>=3.0.0`.
+pub(crate) fn version_requirement(text: &str) -> Option<String> {
+    let captures = VERSION_TRIGGER.captures(text)?;
+    parse_version(&captures[2]).ok()?;
+    Some(format!("{}{}", &captures[1], &captures[2]))
+}
+
+/// Whether `current` satisfies a requirement such as `>2.1`.
+///
+/// Unlike cargo's requirements, missing numbers are zeros, so `>2.1` is met by `2.1.5`, and
+/// versions are ordered by semver precedence, so `>=2.0.0-rc.1` is met by `2.1.0-beta`. `=` only
+/// compares the numbers written, so `=2.1` is met by any `2.1.x` release.
+pub(crate) fn is_reached(requirement: &str, current: &Version) -> bool {
+    let written = requirement.trim_start_matches(['<', '>', '=']);
+    let operator = &requirement[..requirement.len() - written.len()];
+    let Ok(target) = parse_version(written) else {
+        return false;
+    };
+
+    let ordering = current.cmp_precedence(&target);
+    match operator {
+        ">=" => ordering.is_ge(),
+        "<=" => ordering.is_le(),
+        ">" => ordering.is_gt(),
+        "<" => ordering.is_lt(),
+        _ if written.contains('-') => ordering == Ordering::Equal,
+        _ => match written.matches('.').count() {
+            0 => current.major == target.major && current.pre.is_empty(),
+            1 => {
+                (current.major, current.minor) == (target.major, target.minor)
+                    && current.pre.is_empty()
+            }
+            _ => ordering == Ordering::Equal,
+        },
+    }
+}
+
+/// Reads the current version of the project from `source`, a file path or `git_tag`.
+///
+/// An empty source tries `Cargo.toml`, `package.json` and `VERSION` in order, skipping one that
+/// declares no version such as a workspace root, then the git tag.
+pub(crate) fn current_version(source: &str) -> Result<Version, Error> {
+    match source {
+        "" => detected_version(Path::new(""), git_tag_version),
+        GIT_TAG_SOURCE => git_tag_version(),
+        file => {
+            let version = declared_version(Path::new(file))?
+                .ok_or_else(|| anyhow!("no version declared in `{}`", file))?;
+            parse_file_version(Path::new(file), &version)
+        }
+    }
+}
+
+fn detected_version(
+    directory: &Path,
+    fallback: impl FnOnce() -> Result<Version, Error>,
+) -> Result<Version, Error> {
+    let files = AUTO_DETECTED_FILES
+        .iter()
+        .map(|file| directory.join(file))
+        .filter(|file| file.is_file());
+    for file in files {
+        if let Some(version) = declared_version(&file)? {
+            return parse_file_version(&file, &version);
+        }
+    }
+    fallback()
+}
+
+/// The version `file` declares, or `None` when a manifest has no version field.
+fn declared_version(file: &Path) -> Result<Option<String>, Error> {
+    let content = fs::read_to_string(file).map_err(|e| {
+        anyhow!(
+            "failed to read the version from `{}`: {}",
+            file.display(),
+            e
+        )
+    })?;
+    let version = match file.file_name().and_then(|name| name.to_str()) {
+        Some("Cargo.toml") => cargo_version(&content),
+        Some("package.json") => package_json_version(&content),
+        _ => Ok(Some(content.trim().to_string())),
+    };
+    version.map_err(|e| anyhow!("failed to read `{}`: {}", file.display(), e))
+}
+
+fn parse_file_version(file: &Path, version: &str) -> Result<Version, Error> {
+    parse_version(version).map_err(|e| anyhow!("invalid version in `{}`: {}", file.display(), e))
+}
+
+/// The version of `[package]`, or of `[workspace.package]` for a workspace root.
+fn cargo_version(content: &str) -> Result<Option<String>, Error> {
+    let manifest: toml::Table = toml::from_str(content)?;
+    let version = |table: Option<&toml::Value>| {
+        table?
+            .get("version")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    };
+
+    Ok(version(manifest.get("package"))
+        .or_else(|| version(manifest.get("workspace").and_then(|w| w.get("package")))))
+}
+
+fn package_json_version(content: &str) -> Result<Option<String>, Error> {
+    let package: serde_json::Value = serde_json::from_str(content)?;
+    Ok(package["version"].as_str().map(str::to_string))
+}
+
+fn git_tag_version() -> Result<Version, Error> {
+    let tag = Repo::discover()?.nearest_tag()?;
+
+    parse_version(&tag).map_err(|e| anyhow!("invalid version in git tag `{}`: {}", tag, e))
+}
+
+/// Parses a version, allowing a `v` prefix as in git tags and missing minor or patch numbers
+/// as in `VERSION` files or Python packages.
+pub(super) fn parse_version(version: &str) -> Result<Version, semver::Error> {
+    let version = version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    let end = version.find(['-', '+']).unwrap_or(version.len());
+    let (core, suffix) = version.split_at(end);
+    let padding = ".0".repeat(2usize.saturating_sub(core.matches('.').count()));
+    Version::parse(&format!("{}{}{}", core, padding, suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_version_requirement() {
+        let requirement = version_requirement("// remind: version>=3.0.0 drop the v2 API");
+        assert_eq!(requirement.as_deref(), Some(">=3.0.0"));
+
+        let requirement = version_requirement("// remind: version > 2.1 remove");
+        assert_eq!(requirement.as_deref(), Some(">2.1"));
+
+        assert_eq!(version_requirement("// remind: 2024/06/27"), None);
+        assert_eq!(version_requirement("// remind: conversion>=3.0.0"), None);
+    }
+
+    #[test]
+    fn test_is_reached() {
+        let current = Version::parse("3.1.0").unwrap();
+        assert!(is_reached(">=3.0.0", &current));
+        assert!(!is_reached(">=3.2", &current));
+        assert!(is_reached("<4", &current));
+    }
+
+    #[test]
+    fn test_is_reached_pads_and_orders_prereleases() {
+        let patch = Version::parse("2.1.5").unwrap();
+        assert!(is_reached(">2.1", &patch));
+        assert!(!is_reached("<=2.1", &patch));
+        assert!(is_reached("=2.1", &patch));
+        assert!(!is_reached("=2.1.0", &patch));
+
+        let rc = Version::parse("2.0.0-rc.1").unwrap();
+        assert!(is_reached(">=2.0.0-rc.1", &rc));
+        assert!(is_reached(">=1.0", &rc));
+        assert!(!is_reached(">=2.0.0", &rc));
+        assert!(!is_reached("=2", &rc));
+        assert!(is_reached(">=2.0.0-beta", &rc));
+        assert!(is_reached(
+            ">=2.0.0-rc.1",
+            &Version::parse("2.1.0-beta").unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_manifest_versions() {
+        let cargo = "[package]\nname = \"app\"\nversion = \"1.2.3\"\n";
+        assert_eq!(cargo_version(cargo).unwrap().as_deref(), Some("1.2.3"));
+
+        let workspace = "[workspace]\nmembers = []\n[workspace.package]\nversion = \"2.0.0\"\n";
+        assert_eq!(cargo_version(workspace).unwrap().as_deref(), Some("2.0.0"));
+
+        let package = r#"{"name": "app", "version": "0.4.0"}"#;
+        assert_eq!(
+            package_json_version(package).unwrap().as_deref(),
+            Some("0.4.0")
+        );
+
+        assert_eq!(parse_version("v1.0.0").unwrap(), Version::new(1, 0, 0));
+        assert_eq!(parse_version("2.1").unwrap(), Version::new(2, 1, 0));
+        assert_eq!(
+            parse_version("3-rc.1").unwrap(),
+            Version::parse("3.0.0-rc.1").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_detected_version_skips_unversioned_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        let directory = dir.path();
+        fs::write(
+            directory.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"core\"]\n",
+        )
+        .unwrap();
+        let tag = || Ok(Version::new(4, 0, 0));
+
+        // A workspace root without `[workspace.package]` falls through to the git tag.
+        assert_eq!(
+            detected_version(directory, tag).unwrap(),
+            Version::new(4, 0, 0)
+        );
+
+        fs::write(directory.join("package.json"), r#"{"version": "1.5.0"}"#).unwrap();
+        assert_eq!(
+            detected_version(directory, tag).unwrap(),
+            Version::new(1, 5, 0)
+        );
+    }
+}
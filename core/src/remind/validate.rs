@@ -0,0 +1,306 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, bail, Error};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::datetime::{parse_datetime, validate_format_to_regex, DatetimeMatcher};
+use super::Remind;
+use crate::config::builder::ValidateItem;
+
+/// An entry of `validates` that a reminder does not pass.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationFailure {
+    /// The name of the entry in `validates`.
+    pub name: String,
+    /// The meta field that was checked, or `None` for the message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<String>,
+    /// The value of the meta field, or `None` when it is absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub found: Option<String>,
+    /// What was expected instead, e.g. the format the message does not contain.
+    pub expected: String,
+}
+
+/// The entries of `validates`, with their patterns compiled once per run.
+pub struct Validator {
+    checks: BTreeMap<String, Check>,
+}
+
+struct Check {
+    item: ValidateItem,
+    format: Option<Regex>,
+    date: Option<DatetimeMatcher>,
+}
+
+impl Validator {
+    /// Compiles `validates`, rejecting entries that check nothing or ask a message for what
+    /// only a meta field has.
+    pub fn new(validates: &HashMap<String, ValidateItem>) -> Result<Self, Error> {
+        let checks = validates
+            .iter()
+            .map(|(name, item)| {
+                let check = Check::new(item).map_err(|e| anyhow!("`{}`: {}", name, e))?;
+                Ok((name.clone(), check))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Self { checks })
+    }
+
+    /// The entries `remind` does not pass, in the order of their names.
+    pub fn validate(&self, remind: &Remind) -> Vec<ValidationFailure> {
+        self.checks
+            .iter()
+            .filter_map(|(name, check)| check.failure(name, remind))
+            .collect()
+    }
+}
+
+impl Check {
+    fn new(item: &ValidateItem) -> Result<Self, Error> {
+        let on_meta = !item.meta.is_empty();
+        if !on_meta && (item.required || !item.allowed.is_empty()) {
+            bail!("`required` and `allowed` need a `meta` field to check");
+        }
+        if item.format.is_empty()
+            && item.date_format.is_empty()
+            && item.allowed.is_empty()
+            && !item.required
+        {
+            bail!("nothing to check, expected `format`, `date_format`, `allowed` or `required`");
+        }
+
+        let format = match (item.format.as_str(), on_meta) {
+            ("", _) => None,
+            // A meta value is checked as a whole, while a message only has to contain the format.
+            (format, true) => Some(format!("^(?:{})$", validate_format_to_regex(format)?)),
+            (format, false) => Some(validate_format_to_regex(format)?),
+        };
+        let date = match item.date_format.as_str() {
+            "" => None,
+            format => Some(DatetimeMatcher::new(format)?),
+        };
+
+        Ok(Self {
+            item: item.clone(),
+            format: format.map(|format| Regex::new(&format)).transpose()?,
+            date,
+        })
+    }
+
+    fn failure(&self, name: &str, remind: &Remind) -> Option<ValidationFailure> {
+        let item = &self.item;
+        let failure = |found: Option<&str>, expected: String| ValidationFailure {
+            name: name.to_string(),
+            meta: (!item.meta.is_empty()).then(|| item.meta.clone()),
+            found: found.map(str::to_string),
+            expected,
+        };
+
+        if item.meta.is_empty() {
+            if self
+                .format
+                .as_ref()
+                .is_some_and(|r| !r.is_match(&remind.message))
+            {
+                return Some(failure(None, item.format.clone()));
+            }
+            let message = remind.message.as_str();
+            let has_date = |date: &DatetimeMatcher| {
+                date.find_iter(message, 0)
+                    .any(|found| parse_datetime(&message[found], &item.date_format).is_ok())
+            };
+            if self.date.as_ref().is_some_and(|date| !has_date(date)) {
+                return Some(failure(None, item.date_format.clone()));
+            }
+            return None;
+        }
+
+        let Some(value) = remind.meta.get(&item.meta).map(|v| v.as_str()) else {
+            return item.required.then(|| failure(None, "a value".to_string()));
+        };
+        if self.format.as_ref().is_some_and(|r| !r.is_match(value)) {
+            return Some(failure(
+                Some(value),
+                format!("a value matching `{}`", item.format),
+            ));
+        }
+        let is_date = |date: &DatetimeMatcher| {
+            date.find(value) == Some(0..value.len())
+                && parse_datetime(value, &item.date_format).is_ok()
+        };
+        if self.date.as_ref().is_some_and(|date| !is_date(date)) {
+            return Some(failure(
+                Some(value),
+                format!("a date like `{}`", item.date_format),
+            ));
+        }
+        if !item.allowed.is_empty() && !item.allowed.iter().any(|allowed| allowed == value) {
+            return Some(failure(
+                Some(value),
+                format!("one of {}", item.allowed.join(", ")),
+            ));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::builder::ConfigBuilder;
+    use crate::remind::{scan_text, Blame, MetaValue};
+    use pretty_assertions::assert_eq;
+
+    fn item(meta: &str) -> ValidateItem {
+        ValidateItem {
+            meta: meta.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_meta() {
+        let validates = HashMap::from([
+            (
+                "roster".to_string(),
+                ValidateItem {
+                    allowed: vec!["alice".into(), "bob".into()],
+                    required: true,
+                    ..item("assignee")
+                },
+            ),
+            (
+                "ticket".to_string(),
+                ValidateItem {
+                    format: r"[A-Z]+-\d+".into(),
+                    ..item("ticket")
+                },
+            ),
+            (
+                "date".to_string(),
+                ValidateItem {
+                    date_format: "%Y/%m/%d".into(),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let validator = Validator::new(&validates).unwrap();
+
+        let config = ConfigBuilder::new().build().unwrap();
+        let mut remind = scan_text(&config, "src/main.rs", "// remind: 2024/06/27 drop\n")
+            .unwrap()
+            .reminds
+            .remove(0);
+
+        let failures = validator.validate(&remind);
+        assert_eq!(
+            failures,
+            vec![ValidationFailure {
+                name: "roster".into(),
+                meta: Some("assignee".into()),
+                found: None,
+                expected: "a value".into(),
+            }]
+        );
+
+        remind
+            .meta
+            .insert("assignee".into(), MetaValue::Text("carol".into()));
+        remind
+            .meta
+            .insert("ticket".into(), MetaValue::Text("OPS-12 and more".into()));
+        remind.message = "// remind: 2024/02/30 drop\n".into();
+        let failures = validator.validate(&remind);
+        assert_eq!(
+            failures
+                .iter()
+                .map(|f| (f.name.as_str(), f.found.as_deref(), f.expected.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("date", None, "%Y/%m/%d"),
+                ("roster", Some("carol"), "one of alice, bob"),
+                (
+                    "ticket",
+                    Some("OPS-12 and more"),
+                    r"a value matching `[A-Z]+-\d+`"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_assignee_from_blame() {
+        let validates = HashMap::from([(
+            "owner".to_string(),
+            ValidateItem {
+                required: true,
+                ..item("assignee")
+            },
+        )]);
+        let validator = Validator::new(&validates).unwrap();
+
+        let config = ConfigBuilder::new().build().unwrap();
+        let mut remind = scan_text(&config, "src/main.rs", "// remind: 2024/06/27 drop\n")
+            .unwrap()
+            .reminds
+            .remove(0);
+        assert_eq!(validator.validate(&remind).len(), 1);
+
+        remind.attribute(Blame {
+            author_name: "alice".into(),
+            author_email: "alice@example.com".into(),
+            commit_id: "0".repeat(40),
+            commit_time: 1719446400,
+        });
+        assert_eq!(validator.validate(&remind), vec![]);
+    }
+
+    #[test]
+    fn test_validator_rejects_unusable_items() {
+        let new = |item: ValidateItem| {
+            Validator::new(&HashMap::from([("x".to_string(), item)]))
+                .err()
+                .map(|e| e.to_string())
+        };
+
+        assert!(new(item("assignee")).unwrap().contains("nothing to check"));
+        let required = ValidateItem {
+            required: true,
+            ..Default::default()
+        };
+        assert!(new(required).unwrap().contains("need a `meta` field"));
+        let format = ValidateItem {
+            format: "@(alice".into(),
+            ..Default::default()
+        };
+        assert!(new(format).is_some());
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_meta() {
+        let dir = tempfile::tempdir().unwrap();
+        let build = |yaml: &str| {
+            let path = dir.path().join("remind.yml");
+            std::fs::write(&path, yaml).unwrap();
+            ConfigBuilder::new()
+                .config_file_path(Some(path.to_string_lossy().into_owned()))
+                .build()
+                .err()
+                .map(|e| e.to_string())
+        };
+        let config = |meta: &str| {
+            format!(
+                "comment_regex: \"(@${{assignee:user}} )?remind:\"\n\
+                 validates:\n  roster:\n    meta: {}\n    required: true\n",
+                meta
+            )
+        };
+
+        assert_eq!(build(&config("assignee")), None);
+        let error = build(&config("asignee")).unwrap();
+        assert!(error.contains("`asignee` is not a placeholder in comment_regex"));
+    }
+}